@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS `ix_contents_codec_id`;
+
+ALTER TABLE `contents` DROP COLUMN `dictionary_id`;
+ALTER TABLE `contents` DROP COLUMN `codec`;
@@ -0,0 +1,4 @@
+ALTER TABLE `contents` ADD COLUMN `codec` INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE `contents` ADD COLUMN `dictionary_id` INTEGER;  -- FK
+
+CREATE INDEX `ix_contents_codec_id` ON `contents` (`codec`, `id`);
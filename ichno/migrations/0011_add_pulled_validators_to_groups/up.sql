@@ -0,0 +1,2 @@
+ALTER TABLE `groups` ADD COLUMN `pulled_etag` VARCHAR(512);  -- the ETag of the remote DB last pulled
+ALTER TABLE `groups` ADD COLUMN `pulled_last_modified` VARCHAR(64);  -- the Last-Modified of the remote DB last pulled
@@ -1,25 +1,42 @@
 use std::{
+    collections::{BTreeMap, BTreeSet},
     convert::AsRef,
-    error::Error,
+    fs::{self, File},
+    io::{Read, Write},
     path::{Path, PathBuf},
 };
 
 use chrono::{DateTime, NaiveDateTime, Utc};
-use diesel::sqlite::SqliteConnection;
-
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use url::Url;
 
 use crate::{
-    constants::GroupType,
-    db::actions::{
-        create_group_if_needed, create_workspace_if_needed, update_meta_group_stat, update_stat_with_paths_if_needed,
+    codec::{ContentEncoder, DEFAULT_LEVEL},
+    constants::{ContentType, GroupType, Status, ATTR_GROUP_NAME},
+    db::{
+        actions::{
+            calc_digest, create_attr_and_stat_with_bytes_if_needed, create_content_if_needed, create_group_if_needed,
+            create_workspace_if_needed, decode_content_body, disable_attr_and_stat_if_needed, find_attr_history_at,
+            find_content_encoder, find_history_with_content, is_source_recreated, recompress_contents,
+            select_attr_histories, source_id, train_content_dictionary, update_meta_group_stat,
+            update_stat_with_paths_if_needed, AttrValue,
+        },
+        Attrs, Connection, Contents, Footprints, Groups, Histories, SnapshotCondition, Stats, Workspaces,
     },
-    error::DomainError,
-    models::{Group, Stat, Workspace},
+    error::{DomainError, DomainResult},
+    export::{write_record, ContentRecord, Footer, Header, Record},
+    extract::{AttrExtractor, HEAD_SIZE},
+    models::{Attr, Content, Footprint, Group, History, Stat, Workspace},
+    push::{Changeset, PushClient, PushResult},
+    store::{self, ContentStore},
 };
 
+const ATTR_SUMMARY_SIZE_MAX: usize = 512;
+const PUSH_BATCH_SIZE: i64 = 1000;
+const EXPORT_BATCH_SIZE: i64 = 1000;
+
 pub struct Context<'c, 'a> {
-    pub connection: &'c mut SqliteConnection,
+    pub connection: &'c mut Connection,
     pub db_path: &'a Path,
     pub workspace_name: &'a str,
     pub workspace: Option<Workspace>,
@@ -46,7 +63,36 @@ impl<'c, 'a> Context<'c, 'a> {
     }
 }
 
-pub fn pre_process(ctx: &mut Context) -> Result<(), Box<dyn Error>> {
+#[derive(Debug, Default)]
+pub struct ContentPolicy {
+    pub size_max: i64,
+    pub globs: Option<GlobSet>,
+    pub store: Option<Box<dyn ContentStore>>,
+    pub encoder: ContentEncoder,
+}
+
+impl ContentPolicy {
+    pub fn new(size_max: i64, patterns: &[String]) -> DomainResult<ContentPolicy> {
+        let globs = if patterns.is_empty() {
+            None
+        } else {
+            let mut builder = GlobSetBuilder::new();
+            for pattern in patterns.iter() {
+                builder.add(Glob::new(pattern).map_err(|e| DomainError::params("glob", e.to_string()))?);
+            }
+            Some(builder.build().map_err(|e| DomainError::params("glob", e.to_string()))?)
+        };
+        Ok(ContentPolicy { size_max, globs, store: None, encoder: Default::default() })
+    }
+
+    pub fn is_target(&self, stat: &Stat) -> bool {
+        stat.status == Status::Enabled
+            && stat.size.is_some_and(|size| size <= self.size_max)
+            && self.globs.as_ref().is_none_or(|globs| globs.is_match(&stat.path))
+    }
+}
+
+pub fn pre_process(ctx: &mut Context) -> DomainResult<()> {
     let now = ctx.naive_current_time();
     let workspace = create_workspace_if_needed(ctx.connection, ctx.workspace_name, now)?;
     ctx.workspace = Some(workspace.clone());
@@ -56,7 +102,18 @@ pub fn pre_process(ctx: &mut Context) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-pub fn post_process(ctx: &mut Context) -> Result<(), Box<dyn Error>> {
+/// Looks up the workspace and the group without creating them, for commands that only read the DB.
+pub fn find_workspace_and_group(ctx: &mut Context) -> DomainResult<()> {
+    let workspace = Workspaces::find_by_name(ctx.connection, ctx.workspace_name)?
+        .ok_or_else(|| DomainError::not_found("workspace", format!("no such workspace: {}", ctx.workspace_name)))?;
+    let group = Groups::find_by_name(ctx.connection, workspace.id, ctx.group_name)?
+        .ok_or_else(|| DomainError::not_found("group", format!("no such group: {}", ctx.group_name)))?;
+    ctx.workspace = Some(workspace);
+    ctx.group = Some(group);
+    Ok(())
+}
+
+pub fn post_process(ctx: &mut Context) -> DomainResult<()> {
     let now = ctx.naive_current_time();
     let workspace = ctx.workspace.as_ref().unwrap();
     let target_group = ctx.group.as_ref().unwrap();
@@ -65,16 +122,498 @@ pub fn post_process(ctx: &mut Context) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-pub fn update_file_stat<P: AsRef<Path>>(ctx: &mut Context, path: P) -> Result<Option<Stat>, Box<dyn Error>> {
+fn resolve_path<P: AsRef<Path>>(ctx: &Context, path: P) -> DomainResult<(PathBuf, String)> {
+    let base_path = ctx.base_directory().unwrap();
+    let path = if path.as_ref().is_absolute() { PathBuf::from(path.as_ref()) } else { base_path.join(path) };
+    let path_ref = path
+        .strip_prefix(base_path)
+        .map_err(|_| DomainError::params("path", format!("out of base directory: {:?}", path)))?;
+    let path_str = if let Some(s) = path_ref.to_str() {
+        s.to_owned()
+    } else {
+        return Err(DomainError::params("path", format!("can't convert to UTF-8: {:?}", path_ref)));
+    };
+    Ok((path_ref.to_owned(), path_str))
+}
+
+pub fn update_file_stat<P: AsRef<Path>>(ctx: &mut Context, path: P) -> DomainResult<Option<Stat>> {
     let group = ctx.group.as_ref().unwrap();
     let now = ctx.naive_current_time();
+    let (path_ref, path_str) = resolve_path(ctx, path)?;
+    update_stat_with_paths_if_needed(ctx.connection, group, &path_str, &path_ref, now)
+}
+
+pub fn update_file_content<P: AsRef<Path>>(
+    ctx: &mut Context,
+    policy: &ContentPolicy,
+    stat: &Stat,
+    path: P,
+) -> DomainResult<Option<Content>> {
+    if !policy.is_target(stat) {
+        return Ok(None);
+    }
+    let (footprint_id, digest) = if let (Some(footprint_id), Some(digest)) = (stat.footprint_id, stat.digest.as_ref()) {
+        (footprint_id, digest)
+    } else {
+        return Ok(None);
+    };
+    let content = Contents::find_by_footprint_id(ctx.connection, footprint_id)?;
+    if content.is_some() {
+        return Ok(content);
+    }
+    let now = ctx.naive_current_time();
     let base_path = ctx.base_directory().unwrap();
     let path = if path.as_ref().is_absolute() { PathBuf::from(path.as_ref()) } else { base_path.join(path) };
-    let path_ref = path.strip_prefix(base_path)?;
-    let path_str = if let Some(s) = path_ref.to_str() {
-        s
+    let bytes = fs::read(path)?;
+    let mut slice = bytes.as_slice();
+    if &calc_digest(&mut slice)? != digest {
+        warn!("content skipped because the file was modified: {}", &stat.path);
+        return Ok(None);
+    }
+    let content = if let Some(store) = policy.store.as_ref() {
+        let location = store.put(digest, &bytes)?;
+        create_content_if_needed(ctx.connection, footprint_id, &[], Some(&location), &policy.encoder, now)?
     } else {
-        return Err(Box::new(DomainError::params("path", format!("can't convert to UTF-8: {:?}", path_ref))));
+        create_content_if_needed(ctx.connection, footprint_id, &bytes, None, &policy.encoder, now)?
+    };
+    Ok(Some(content))
+}
+
+fn load_content_body(ctx: &mut Context, content: &Content) -> DomainResult<Vec<u8>> {
+    if let Some(location) = content.location.as_ref() {
+        store::load(location)
+    } else {
+        decode_content_body(ctx.connection, content)
+    }
+}
+
+pub fn load_content_encoder(ctx: &mut Context, level: i32) -> DomainResult<ContentEncoder> {
+    find_content_encoder(ctx.connection, level)
+}
+
+pub fn find_file_content<P: AsRef<Path>>(
+    ctx: &mut Context,
+    path: P,
+    version: Option<i32>,
+) -> DomainResult<(History, Vec<u8>)> {
+    let group = ctx.group.as_ref().unwrap();
+    let (_, path_str) = resolve_path(ctx, path)?;
+    let (history, content) = match find_history_with_content(ctx.connection, group, &path_str, version)? {
+        Some((history, Some(content))) => (history, content),
+        Some((history, None)) if history.status == Status::Disabled => {
+            return Err(DomainError::params(
+                "path",
+                format!("file is absent in version {}: {}", history.version, &path_str),
+            ))
+        }
+        Some((history, None)) => {
+            return Err(DomainError::params(
+                "path",
+                format!("content is not stored in version {}: {}", history.version, &path_str),
+            ))
+        }
+        None => return Err(DomainError::params("path", format!("no such version: {}", &path_str))),
+    };
+    let body = load_content_body(ctx, &content)?;
+    let mut slice = body.as_slice();
+    if Some(calc_digest(&mut slice)?) != history.digest {
+        return Err(DomainError::internal(
+            "content",
+            format!("content digest mismatch in version {}: {}", history.version, &path_str),
+        ));
+    }
+    Ok((history, body))
+}
+
+#[derive(Debug, Default)]
+pub struct RestoreOptions<'a> {
+    pub destination: Option<&'a Path>,
+    pub force: bool,
+}
+
+pub fn restore_file<P: AsRef<Path>>(
+    ctx: &mut Context,
+    path: P,
+    version: Option<i32>,
+    options: &RestoreOptions,
+) -> DomainResult<History> {
+    let (history, body) = find_file_content(ctx, path.as_ref(), version)?;
+    let group = ctx.group.as_ref().unwrap();
+    let base_path = ctx.base_directory().unwrap();
+    let destination = options.destination.unwrap_or(path.as_ref());
+    let destination = if destination.is_absolute() { destination.to_owned() } else { base_path.join(destination) };
+    if let Ok(mut f) = File::open(&destination) {
+        let digest = calc_digest(&mut f)?;
+        if Some(&digest) == history.digest.as_ref() {
+            info!("already restored: {:?}", &destination);
+            return Ok(history);
+        }
+        if !options.force {
+            let stat = if let Ok(stat_path) = destination.strip_prefix(&base_path) {
+                Stats::find_by_path(ctx.connection, group.id, &stat_path.to_string_lossy())?
+            } else {
+                None
+            };
+            if stat.and_then(|s| s.digest) != Some(digest) {
+                return Err(DomainError::params(
+                    "destination",
+                    format!("file is modified since the last scan: {:?}", &destination),
+                ));
+            }
+        }
+    }
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&destination, &body)?;
+    info!("restored: {:?} (version {})", &destination, history.version);
+    Ok(history)
+}
+
+#[derive(Debug)]
+pub struct RecompressOptions {
+    pub level: i32,
+    pub train_dictionary: bool,
+    pub dictionary_size: usize,
+    pub sample_size_max: usize,
+}
+
+pub fn recompress(ctx: &mut Context, options: &RecompressOptions) -> DomainResult<(i64, i64)> {
+    let now = ctx.naive_current_time();
+    if options.train_dictionary
+        && train_content_dictionary(ctx.connection, options.dictionary_size, options.sample_size_max, now)?.is_none()
+    {
+        warn!("dictionary is not trained because there are no samples");
+    }
+    let encoder = find_content_encoder(ctx.connection, options.level)?;
+    let (count, updated_count) = recompress_contents(ctx.connection, &encoder)?;
+    info!("recompressed: {} / {}", updated_count, count);
+    Ok((count, updated_count))
+}
+
+fn find_file_footprint<P: AsRef<Path>>(ctx: &mut Context, path: P) -> DomainResult<Footprint> {
+    let group = ctx.group.as_ref().unwrap();
+    let (_, path_str) = resolve_path(ctx, path)?;
+    let stat = Stats::find_by_path(ctx.connection, group.id, &path_str)?;
+    let footprint_id = match stat {
+        Some(Stat { status: Status::Enabled, footprint_id: Some(footprint_id), .. }) => footprint_id,
+        _ => return Err(DomainError::params("path", format!("file is not scanned: {}", &path_str))),
+    };
+    let footprint = Footprints::find(ctx.connection, footprint_id)?;
+    Ok(footprint.unwrap())
+}
+
+fn summarize_attr_value(value: &[u8], content_type: ContentType) -> DomainResult<Option<String>> {
+    let summary = match content_type {
+        ContentType::Json => match serde_json::from_slice::<serde_json::Value>(value)? {
+            serde_json::Value::String(s) => Some(s),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            serde_json::Value::Bool(b) => Some(b.to_string()),
+            _ => None,
+        },
+        ContentType::Text => std::str::from_utf8(value).ok().map(|s| s.trim_end().to_owned()),
+        ContentType::Unknown => None,
+    };
+    Ok(summary.filter(|s| s.len() <= ATTR_SUMMARY_SIZE_MAX))
+}
+
+pub fn set_file_attr<P: AsRef<Path>>(
+    ctx: &mut Context,
+    path: P,
+    key: &str,
+    value: &[u8],
+    content_type: ContentType,
+) -> DomainResult<Attr> {
+    if key.is_empty() || key.contains('/') {
+        return Err(DomainError::params("key", format!("invalid key: {}", key)));
+    }
+    let summary = summarize_attr_value(value, content_type)
+        .map_err(|e| DomainError::params("value", format!("invalid value: {}", e)))?;
+    let target = find_file_footprint(ctx, path)?;
+    let workspace = ctx.workspace.as_ref().unwrap();
+    let now = ctx.naive_current_time();
+    let encoder = find_content_encoder(ctx.connection, DEFAULT_LEVEL)?;
+    let attr_value = AttrValue { key, value, value_content_type: content_type, value_summary: summary.as_deref() };
+    let (attr, _, _) =
+        create_attr_and_stat_with_bytes_if_needed(ctx.connection, workspace, &target, &attr_value, &encoder, now)?;
+    info!("attr set: {}: {}: {}", &attr.target_digest, &attr.key, &attr.value_digest);
+    Ok(attr)
+}
+
+#[derive(Debug)]
+pub struct ExtractPolicy {
+    pub extractors: Vec<Box<dyn AttrExtractor>>,
+    pub encoder: ContentEncoder,
+}
+
+pub fn extract_file_attrs<P: AsRef<Path>>(
+    ctx: &mut Context,
+    policy: &ExtractPolicy,
+    stat: &Stat,
+    path: P,
+) -> DomainResult<Vec<Attr>> {
+    let footprint_id = match stat {
+        Stat { status, footprint_id: Some(footprint_id), .. } if *status == Status::Enabled => *footprint_id,
+        _ => return Ok(vec![]),
+    };
+    let workspace = ctx.workspace.as_ref().unwrap();
+    let existing_attrs = Attrs::select_by_target_footprint_id(ctx.connection, workspace.id, footprint_id)?;
+    let extractors: Vec<_> = policy
+        .extractors
+        .iter()
+        .filter(|e| {
+            !existing_attrs.iter().any(|attr| attr.status == Status::Enabled && e.keys().contains(&attr.key.as_str()))
+        })
+        .collect();
+    if extractors.is_empty() {
+        return Ok(vec![]);
+    }
+    let base_path = ctx.base_directory().unwrap();
+    let path = if path.as_ref().is_absolute() { PathBuf::from(path.as_ref()) } else { base_path.join(path) };
+    let mut head = Vec::with_capacity(HEAD_SIZE);
+    File::open(&path)?.take(HEAD_SIZE as u64).read_to_end(&mut head)?;
+    let target = Footprints::find(ctx.connection, footprint_id)?.unwrap();
+    let now = ctx.naive_current_time();
+    let mut attrs = vec![];
+    for extractor in extractors {
+        let extracted_attrs = match extractor.extract(&path, &head) {
+            Ok(extracted_attrs) => extracted_attrs,
+            Err(e) => {
+                warn!("{}: {:?}: {}", extractor.name(), &path, e);
+                continue;
+            }
+        };
+        for extracted in extracted_attrs {
+            let value = serde_json::to_vec(&extracted.value)?;
+            let summary = extracted.summary.filter(|s| s.len() <= ATTR_SUMMARY_SIZE_MAX);
+            let attr_value = AttrValue {
+                key: &extracted.key,
+                value: &value,
+                value_content_type: ContentType::Json,
+                value_summary: summary.as_deref(),
+            };
+            let (attr, _, _) = create_attr_and_stat_with_bytes_if_needed(
+                ctx.connection,
+                workspace,
+                &target,
+                &attr_value,
+                &policy.encoder,
+                now,
+            )?;
+            debug!("attr extracted: {}: {}: {:?}", &stat.path, &attr.key, &attr.value_summary);
+            attrs.push(attr);
+        }
+    }
+    Ok(attrs)
+}
+
+pub fn find_file_attr<P: AsRef<Path>>(ctx: &mut Context, path: P, key: &str) -> DomainResult<(Attr, Vec<u8>)> {
+    let target = find_file_footprint(ctx, path)?;
+    let workspace = ctx.workspace.as_ref().unwrap();
+    let attr = Attrs::find_by_target_footprint_id_and_key(ctx.connection, workspace.id, target.id, key)?;
+    let attr = match attr {
+        Some(attr) if attr.status == Status::Enabled => attr,
+        _ => return Err(DomainError::params("key", format!("no such attr: {}", key))),
     };
-    update_stat_with_paths_if_needed(ctx.connection, group, path_str, path_ref, now)
+    let content = Contents::find_by_footprint_id(ctx.connection, attr.value_footprint_id)?;
+    let content = content
+        .ok_or_else(|| DomainError::internal("attr", format!("attr value is not stored: {}", &attr.value_digest)))?;
+    let body = load_content_body(ctx, &content)?;
+    Ok((attr, body))
+}
+
+pub fn list_file_attrs<P: AsRef<Path>>(ctx: &mut Context, path: P) -> DomainResult<Vec<Attr>> {
+    let target = find_file_footprint(ctx, path)?;
+    let workspace = ctx.workspace.as_ref().unwrap();
+    let attrs = Attrs::select_by_target_footprint_id(ctx.connection, workspace.id, target.id)?;
+    Ok(attrs.into_iter().filter(|attr| attr.status == Status::Enabled).collect())
+}
+
+pub fn list_files_at(ctx: &mut Context, path_prefix: Option<&str>, at: NaiveDateTime) -> DomainResult<Vec<History>> {
+    let group = ctx.group.as_ref().unwrap();
+    let cond = SnapshotCondition { path_prefix, ..Default::default() };
+    Histories::snapshot(ctx.connection, group.id, at, &cond)
+}
+
+pub fn find_files_by_attr(ctx: &mut Context, key: &str, summary: &str) -> DomainResult<Vec<Stat>> {
+    let workspace = ctx.workspace.as_ref().unwrap();
+    let group = ctx.group.as_ref().unwrap();
+    let attrs = Attrs::select_by_key_and_value_summary(ctx.connection, workspace.id, key, summary)?;
+    let mut stats = vec![];
+    for attr in attrs.iter().filter(|attr| attr.status == Status::Enabled) {
+        let found = Stats::select_by_footprint_id(ctx.connection, workspace.id, attr.target_footprint_id)?;
+        stats.extend(found.into_iter().filter(|stat| stat.group_id == group.id && stat.status == Status::Enabled));
+    }
+    stats.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(stats)
+}
+
+pub fn delete_file_attr<P: AsRef<Path>>(ctx: &mut Context, path: P, key: &str) -> DomainResult<Attr> {
+    let target = find_file_footprint(ctx, path)?;
+    let workspace = ctx.workspace.as_ref().unwrap();
+    let now = ctx.naive_current_time();
+    let attr = disable_attr_and_stat_if_needed(ctx.connection, workspace, &target, key, now)?;
+    let attr = attr.ok_or_else(|| DomainError::params("key", format!("no such attr: {}", key)))?;
+    info!("attr deleted: {}: {}", &attr.target_digest, &attr.key);
+    Ok(attr)
+}
+
+pub fn select_file_attr_histories<P: AsRef<Path>>(
+    ctx: &mut Context,
+    path: P,
+    key: &str,
+) -> DomainResult<Vec<(History, Option<Vec<u8>>)>> {
+    let target = find_file_footprint(ctx, path)?;
+    let workspace = ctx.workspace.as_ref().unwrap();
+    let histories = select_attr_histories(ctx.connection, workspace, &target, key)?;
+    let mut results = vec![];
+    for history in histories {
+        let content = if let Some(footprint_id) = history.footprint_id {
+            Contents::find_by_footprint_id(ctx.connection, footprint_id)?
+        } else {
+            None
+        };
+        let body = if let Some(content) = content { Some(load_content_body(ctx, &content)?) } else { None };
+        results.push((history, body));
+    }
+    Ok(results)
+}
+
+pub fn find_file_attr_at<P: AsRef<Path>>(
+    ctx: &mut Context,
+    path: P,
+    key: &str,
+    at: NaiveDateTime,
+) -> DomainResult<(History, Vec<u8>)> {
+    let target = find_file_footprint(ctx, path)?;
+    let workspace = ctx.workspace.as_ref().unwrap();
+    let history = find_attr_history_at(ctx.connection, workspace, &target, key, at)?;
+    let history = history.ok_or_else(|| DomainError::params("key", format!("no such attr at {}: {}", at, key)))?;
+    let content = Contents::find_by_footprint_id(ctx.connection, history.footprint_id.unwrap())?;
+    let content = content.ok_or_else(|| {
+        DomainError::internal("attr", format!("attr value is not stored: {}", history.digest.as_deref().unwrap_or("")))
+    })?;
+    let body = load_content_body(ctx, &content)?;
+    Ok((history, body))
+}
+
+#[derive(Debug)]
+pub struct PushOptions {
+    pub server_url: Url,
+    pub workspace_name: String,
+    pub group_name: String,
+    pub token: Option<String>,
+    pub batch_size: Option<i64>,
+}
+
+pub fn push(ctx: &mut Context, options: &PushOptions) -> DomainResult<PushResult> {
+    let group = ctx.group.as_ref().unwrap();
+    let client =
+        PushClient::new(&options.server_url, &options.workspace_name, &options.group_name, options.token.clone())?;
+    let source_id = source_id(ctx.workspace.as_ref().unwrap());
+    let cursor = client.fetch_cursor()?;
+    let mut result = PushResult { pulled_history_id: cursor.pulled_history_id, ..Default::default() };
+    let mut last_history_id = result.pulled_history_id.unwrap_or(0);
+    let loc_last_history_id = Histories::find_last(ctx.connection)?.map(|h| h.id).unwrap_or(0);
+    if is_source_recreated(cursor.pulled_source_id.as_deref(), &source_id, last_history_id, loc_last_history_id) {
+        warn!("Local DB seems to be recreated; pushing all histories: {}", &options.group_name);
+        last_history_id = 0;
+    }
+    if last_history_id > 0 {
+        info!("resuming push after history {}: {}", last_history_id, &options.group_name);
+    }
+    let batch_size = options.batch_size.unwrap_or(PUSH_BATCH_SIZE);
+    loop {
+        let histories = Histories::select_by_group_ids_after(ctx.connection, &[group.id], last_history_id, batch_size)?;
+        let batch_last_history_id = if let Some(history) = histories.last() { history.id } else { break };
+        let footprint_ids: Vec<i32> = histories.iter().filter_map(|h| h.footprint_id).collect();
+        let footprints = Footprints::select(ctx.connection, &footprint_ids)?;
+        let histories_count = histories.len();
+        let changeset = Changeset {
+            url: group.url.clone(),
+            source_id: source_id.clone(),
+            from_history_id: result.pulled_history_id,
+            histories,
+            footprints,
+        };
+        let batch_result = client.send(&changeset)?;
+        debug!("histories pushed: {} (until {}): {:?}", histories_count, batch_last_history_id, &batch_result);
+        result.pulled_history_id = batch_result.pulled_history_id;
+        result.histories += batch_result.histories;
+        result.stats += batch_result.stats;
+        result.footprints += batch_result.footprints;
+        last_history_id = batch_last_history_id;
+    }
+    info!(
+        "pushed: {}: {} histories, {} stats, {} footprints",
+        &options.group_name, result.histories, result.stats, result.footprints
+    );
+    Ok(result)
+}
+
+#[derive(Default, Debug)]
+pub struct ExportOptions {
+    pub since: Option<i32>,
+    pub batch_size: Option<i64>,
+}
+
+pub fn export<W: Write>(ctx: &mut Context, options: &ExportOptions, w: &mut W) -> DomainResult<Footer> {
+    let now = ctx.naive_current_time();
+    let workspace = ctx.workspace.clone().unwrap();
+    let group = ctx.group.clone().unwrap();
+    let attr_group = Groups::find_by_name(ctx.connection, workspace.id, ATTR_GROUP_NAME)?;
+    write_record(w, &Record::Header(Header::new(&group.name, options.since, now)))?;
+    write_record(w, &Record::Workspace(workspace.clone()))?;
+    let mut group_ids = vec![group.id];
+    write_record(w, &Record::Group(group))?;
+    if let Some(attr_group) = attr_group.as_ref() {
+        group_ids.push(attr_group.id);
+        write_record(w, &Record::Group(attr_group.clone()))?;
+    }
+    let attr_group_id = attr_group.map(|g| g.id);
+    let batch_size = options.batch_size.unwrap_or(EXPORT_BATCH_SIZE);
+    let mut footer = Footer { last_history_id: options.since, histories: 0 };
+    let mut last_history_id = options.since.unwrap_or(0);
+    loop {
+        let histories = Histories::select_by_group_ids_after(ctx.connection, &group_ids, last_history_id, batch_size)?;
+        let batch_last_history_id = if let Some(history) = histories.last() { history.id } else { break };
+        let attr_histories: Vec<&History> = histories.iter().filter(|h| Some(h.group_id) == attr_group_id).collect();
+        let mut footprint_ids: BTreeSet<i32> = histories.iter().filter_map(|h| h.footprint_id).collect();
+        let mut attrs: BTreeMap<i32, Attr> = BTreeMap::new();
+        for path in attr_histories.iter().map(|h| h.path.as_str()).collect::<BTreeSet<_>>() {
+            let (target_digest, key) = if let Some(pair) = path.split_once('/') { pair } else { continue };
+            if let Some(target) = Footprints::find_by_digest(ctx.connection, target_digest)? {
+                footprint_ids.insert(target.id);
+                let attr = Attrs::find_by_target_footprint_id_and_key(ctx.connection, workspace.id, target.id, key)?;
+                if let Some(attr) = attr {
+                    attrs.insert(attr.id, attr);
+                }
+            }
+        }
+        let footprints = Footprints::select(ctx.connection, &footprint_ids.into_iter().collect())?;
+        for footprint in footprints.iter() {
+            write_record(w, &Record::Footprint(footprint.clone()))?;
+        }
+        let value_footprint_ids: BTreeSet<i32> =
+            attr_histories.iter().filter(|h| h.status == Status::Enabled).filter_map(|h| h.footprint_id).collect();
+        for footprint in footprints.iter().filter(|f| value_footprint_ids.contains(&f.id)) {
+            if let Some(content) = Contents::find_by_footprint_id(ctx.connection, footprint.id)? {
+                let body = load_content_body(ctx, &content)?;
+                write_record(w, &Record::Content(ContentRecord::new(footprint, &body)))?;
+            }
+        }
+        for attr in attrs.into_values() {
+            write_record(w, &Record::Attr(attr))?;
+        }
+        footer.histories += histories.len();
+        for history in histories.into_iter() {
+            write_record(w, &Record::History(history))?;
+        }
+        footer.last_history_id = Some(batch_last_history_id);
+        last_history_id = batch_last_history_id;
+        debug!("histories exported: {} (until {})", footer.histories, last_history_id);
+    }
+    write_record(w, &Record::Footer(footer.clone()))?;
+    w.flush()?;
+    Ok(footer)
 }
@@ -0,0 +1,116 @@
+use std::io::Read;
+
+use crate::{
+    constants::ContentCodec,
+    error::{DomainError, DomainResult},
+};
+
+pub const DEFAULT_LEVEL: i32 = 3;
+
+#[derive(Clone, Debug)]
+pub struct ContentDictionary {
+    pub content_id: i32,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ContentEncoder {
+    pub level: i32,
+    pub dictionary: Option<ContentDictionary>,
+}
+
+impl Default for ContentEncoder {
+    fn default() -> Self {
+        ContentEncoder { level: DEFAULT_LEVEL, dictionary: None }
+    }
+}
+
+#[derive(Debug)]
+pub struct EncodedBody {
+    pub codec: ContentCodec,
+    pub dictionary_id: Option<i32>,
+    pub bytes: Vec<u8>,
+}
+
+impl ContentEncoder {
+    pub fn encode(&self, body: &[u8]) -> DomainResult<EncodedBody> {
+        let (codec, dictionary_id, bytes) = if let Some(dictionary) = self.dictionary.as_ref() {
+            let mut compressor = zstd::bulk::Compressor::with_dictionary(self.level, &dictionary.bytes)?;
+            (ContentCodec::ZstdDictionary, Some(dictionary.content_id), compressor.compress(body)?)
+        } else {
+            (ContentCodec::Zstd, None, zstd::bulk::compress(body, self.level)?)
+        };
+        if bytes.len() < body.len() {
+            Ok(EncodedBody { codec, dictionary_id, bytes })
+        } else {
+            Ok(EncodedBody { codec: ContentCodec::Raw, dictionary_id: None, bytes: body.to_vec() })
+        }
+    }
+}
+
+pub fn decode(codec: ContentCodec, body: &[u8], dictionary: Option<&[u8]>) -> DomainResult<Vec<u8>> {
+    match codec {
+        ContentCodec::Raw | ContentCodec::Dictionary => Ok(body.to_vec()),
+        ContentCodec::Zstd => Ok(zstd::stream::decode_all(body)?),
+        ContentCodec::ZstdDictionary => {
+            let dictionary = if let Some(dictionary) = dictionary {
+                dictionary
+            } else {
+                return Err(DomainError::internal("dictionary", "dictionary is missing".to_owned()));
+            };
+            let mut decoder = zstd::stream::read::Decoder::with_dictionary(body, dictionary)?;
+            let mut bytes = Vec::new();
+            decoder.read_to_end(&mut bytes)?;
+            Ok(bytes)
+        }
+    }
+}
+
+pub fn train_dictionary(samples: &[Vec<u8>], size: usize) -> DomainResult<Vec<u8>> {
+    Ok(zstd::dict::from_samples(samples, size)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<Vec<u8>> {
+        (0..200)
+            .map(|i| format!("{{\"id\": {}, \"name\": \"file-{}.txt\", \"tags\": [\"a\", \"b{}\"]}}", i, i * 7, i % 13))
+            .map(String::into_bytes)
+            .collect()
+    }
+
+    #[test]
+    fn test_raw_and_zstd_round_trip() {
+        let encoder = ContentEncoder::default();
+        let encoded = encoder.encode(b"abc").unwrap();
+        assert_eq!(ContentCodec::Raw, encoded.codec);
+        assert_eq!(b"abc", decode(encoded.codec, &encoded.bytes, None).unwrap().as_slice());
+
+        let body = "hello, world\n".repeat(100);
+        let encoded = encoder.encode(body.as_bytes()).unwrap();
+        assert_eq!(ContentCodec::Zstd, encoded.codec);
+        assert_eq!(None, encoded.dictionary_id);
+        assert!(encoded.bytes.len() < body.len());
+        assert_eq!(body.as_bytes(), decode(encoded.codec, &encoded.bytes, None).unwrap().as_slice());
+    }
+
+    #[test]
+    fn test_dictionary_round_trip() {
+        let samples = samples();
+        let dictionary = train_dictionary(&samples, 4096).unwrap();
+        assert_eq!(dictionary, decode(ContentCodec::Dictionary, &dictionary, None).unwrap());
+
+        let encoder = ContentEncoder {
+            level: DEFAULT_LEVEL,
+            dictionary: Some(ContentDictionary { content_id: 42, bytes: dictionary.clone() }),
+        };
+        let body = &samples[100];
+        let encoded = encoder.encode(body).unwrap();
+        assert_eq!(ContentCodec::ZstdDictionary, encoded.codec);
+        assert_eq!(Some(42), encoded.dictionary_id);
+        assert_eq!(body, &decode(encoded.codec, &encoded.bytes, Some(&dictionary)).unwrap());
+        assert!(decode(encoded.codec, &encoded.bytes, None).is_err());
+    }
+}
@@ -1,25 +1,88 @@
-pub const DEFAULT_WORKSPACE_NAME: &'static str = "default";
-pub const DEFAULT_GROUP_NAME: &'static str = "default";
-pub const META_GROUP_NAME: &'static str = "__meta";
-pub const ATTR_GROUP_NAME: &'static str = "__attr";
+use diesel::{
+    backend::Backend,
+    deserialize::{self, FromSql},
+    serialize::{self, Output, ToSql},
+    sql_types::Integer,
+};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug)]
-pub enum Status {
+use crate::error::DomainError;
+
+pub const DEFAULT_WORKSPACE_NAME: &str = "default";
+pub const DEFAULT_GROUP_NAME: &str = "default";
+pub const META_GROUP_NAME: &str = "__meta";
+pub const ATTR_GROUP_NAME: &str = "__attr";
+
+macro_rules! int_enum {
+    ( $name: ident, $code: literal { $( $variant: ident = $value: literal ),+ $(,)? } ) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, AsExpression, FromSqlRow, Serialize, Deserialize)]
+        #[diesel(sql_type = Integer)]
+        #[serde(into = "i32", try_from = "i32")]
+        pub enum $name {
+            $( $variant = $value ),+
+        }
+
+        impl From<$name> for i32 {
+            fn from(value: $name) -> i32 {
+                value as i32
+            }
+        }
+
+        impl TryFrom<i32> for $name {
+            type Error = DomainError;
+
+            fn try_from(value: i32) -> Result<Self, Self::Error> {
+                match value {
+                    $( $value => Ok($name::$variant), )+
+                    _ => Err(DomainError::params($code, format!("unknown {}: {}", $code, value))),
+                }
+            }
+        }
+
+        impl<DB: Backend> ToSql<Integer, DB> for $name
+        where
+            i32: ToSql<Integer, DB>,
+        {
+            fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, DB>) -> serialize::Result {
+                match self {
+                    $( $name::$variant => <i32 as ToSql<Integer, DB>>::to_sql(&$value, out), )+
+                }
+            }
+        }
+
+        impl<DB: Backend> FromSql<Integer, DB> for $name
+        where
+            i32: FromSql<Integer, DB>,
+        {
+            fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+                $name::try_from(i32::from_sql(bytes)?).map_err(|e| e.to_string().into())
+            }
+        }
+    };
+}
+
+int_enum!(Status, "status" {
     Disabled = 0,
     Enabled = 1,
-}
+});
 
-#[derive(Clone, Copy, Debug)]
-pub enum GroupType {
+int_enum!(GroupType, "group_type" {
     Local = 0,
     Remote = 1,
     Meta = 2,
     Attr = 3,
-}
+    Pushed = 4,
+});
 
-#[derive(Clone, Copy, Debug)]
-pub enum ContentType {
+int_enum!(ContentType, "content_type" {
     Unknown = 0,
     Json = 1,
     Text = 2,
-}
+});
+
+int_enum!(ContentCodec, "content_codec" {
+    Raw = 0,
+    Zstd = 1,
+    ZstdDictionary = 2,
+    Dictionary = 3,
+});
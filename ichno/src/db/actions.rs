@@ -1,5 +1,4 @@
 use std::{
-    error::Error,
     fs::File,
     hash::Hasher,
     io::{Read, Seek, SeekFrom, Write},
@@ -12,33 +11,27 @@ use twox_hash::XxHash64;
 use url::Url;
 
 use crate::{
+    codec::{self, ContentDictionary, ContentEncoder, EncodedBody},
     db::{
         config::Connection,
         util::{Attrs, Contents, Footprints, Groups, Histories, Stats, Workspaces},
     },
-    Attr, AttrInsertForm, AttrUpdateForm, Content, ContentInsertForm, Footprint, FootprintInsertForm, Group,
-    GroupInsertForm, GroupType, GroupUpdateForm, History, HistoryInsertForm, Stat, StatInsertForm, StatUpdateForm,
-    Status, Workspace, WorkspaceInsertForm, ATTR_GROUP_NAME, META_GROUP_NAME,
+    error::{DomainError, DomainResult},
+    Attr, AttrInsertForm, AttrUpdateForm, Content, ContentCodec, ContentInsertForm, ContentType, ContentUpdateForm,
+    Footprint, FootprintInsertForm, Group, GroupInsertForm, GroupType, GroupUpdateForm, History, HistoryInsertForm,
+    Stat, StatInsertForm, StatUpdateForm, Status, Workspace, WorkspaceInsertForm, ATTR_GROUP_NAME, META_GROUP_NAME,
 };
 
-pub(crate) fn create_workspace_if_needed(
-    conn: &mut Connection,
-    name: &str,
-    now: NaiveDateTime,
-) -> Result<Workspace, Box<dyn Error>> {
+const CONTENTS_PAGE_SIZE: i64 = 256;
+
+pub fn create_workspace_if_needed(conn: &mut Connection, name: &str, now: NaiveDateTime) -> DomainResult<Workspace> {
     let workspace = Workspaces::find_by_name(conn, name)?;
     Ok(if let Some(workspace) = workspace {
         workspace
     } else {
         let workspace = Workspaces::insert_and_find(
             conn,
-            &WorkspaceInsertForm {
-                name,
-                description: "",
-                status: Status::Enabled as i32,
-                created_at: now,
-                updated_at: now,
-            },
+            &WorkspaceInsertForm { name, description: "", status: Status::Enabled, created_at: now, updated_at: now },
         )?;
         info!("workspace created: {}: {}", workspace.id, &workspace.name);
         trace!("workspace created: {:?}", &workspace);
@@ -46,14 +39,14 @@ pub(crate) fn create_workspace_if_needed(
     })
 }
 
-pub(crate) fn create_group_if_needed(
+pub fn create_group_if_needed(
     conn: &mut Connection,
     workspace: &Workspace,
     name: &str,
     url: &Url,
     type_: GroupType,
     now: NaiveDateTime,
-) -> Result<Group, Box<dyn Error>> {
+) -> DomainResult<Group> {
     let group = Groups::find_by_name(conn, workspace.id, name)?;
     Ok(if let Some(group) = group {
         group
@@ -64,10 +57,14 @@ pub(crate) fn create_group_if_needed(
                 workspace_id: workspace.id,
                 name,
                 url: url.as_str(),
-                type_: type_ as i32,
+                type_,
                 description: "",
-                status: Status::Enabled as i32,
+                status: Status::Enabled,
                 group_stat_id: None,
+                pulled_history_id: None,
+                pulled_etag: None,
+                pulled_last_modified: None,
+                pulled_source_id: None,
                 created_at: now,
                 updated_at: now,
             },
@@ -78,37 +75,55 @@ pub(crate) fn create_group_if_needed(
     })
 }
 
-pub(crate) fn create_meta_group_if_needed(
+pub fn create_meta_group_if_needed(
     conn: &mut Connection,
     workspace: &Workspace,
     now: NaiveDateTime,
-) -> Result<Group, Box<dyn Error>> {
+) -> DomainResult<Group> {
     let group_name = META_GROUP_NAME;
     let url = format!("ichno://{}/{}", workspace.name, group_name);
     let url = Url::parse(&url)?;
     create_group_if_needed(conn, workspace, group_name, &url, GroupType::Meta, now)
 }
 
-#[allow(dead_code)]
-pub(crate) fn create_attr_group_if_needed(
+pub fn create_attr_group_if_needed(
     conn: &mut Connection,
     workspace: &Workspace,
     now: NaiveDateTime,
-) -> Result<Group, Box<dyn Error>> {
+) -> DomainResult<Group> {
     let group_name = ATTR_GROUP_NAME;
     let url = format!("ichno://{}/groups/{}", workspace.name, group_name);
     let url = Url::parse(&url)?;
     create_group_if_needed(conn, workspace, group_name, &url, GroupType::Attr, now)
 }
 
-pub(crate) fn create_history_with_footprint_if_needed(
+/// Identifies a DB by the creation time of its workspace, which changes when the DB is recreated.
+pub fn source_id(workspace: &Workspace) -> String {
+    workspace.created_at.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+}
+
+/// A recreated source DB restarts history IDs from 1, so that the cursor of the last pull or push is meaningless.
+pub fn is_source_recreated(
+    pulled_source_id: Option<&str>,
+    source_id: &str,
+    pulled_history_id: i32,
+    last_history_id: i32,
+) -> bool {
+    match pulled_source_id {
+        Some(pulled_source_id) => pulled_source_id != source_id,
+        // Cursors saved without a source ID can only be compared with the last history ID.
+        None => last_history_id < pulled_history_id,
+    }
+}
+
+pub fn create_history_with_footprint_if_needed(
     conn: &mut Connection,
     group: &Group,
     path: &str,
     footprint: &Footprint,
     mtime: NaiveDateTime,
     now: NaiveDateTime,
-) -> Result<History, Box<dyn Error>> {
+) -> DomainResult<History> {
     let last_history = Histories::find_latest_by_path(conn, group.id, path)?;
     let last_history = if let Some(last_history) = last_history {
         if let Some(last_footprint_id) = last_history.footprint_id {
@@ -131,7 +146,7 @@ pub(crate) fn create_history_with_footprint_if_needed(
             group_id: group.id,
             path,
             version,
-            status: Status::Enabled as i32,
+            status: Status::Enabled,
             mtime: Some(mtime),
             footprint_id: Some(footprint.id),
             digest: Some(&footprint.digest),
@@ -144,15 +159,15 @@ pub(crate) fn create_history_with_footprint_if_needed(
     Ok(history)
 }
 
-pub(crate) fn create_disabled_history_if_needed(
+pub fn create_disabled_history_if_needed(
     conn: &mut Connection,
     group: &Group,
     path: &str,
     now: NaiveDateTime,
-) -> Result<History, Box<dyn Error>> {
+) -> DomainResult<History> {
     let last_history = Histories::find_latest_by_path(conn, group.id, path)?;
     let last_history = if let Some(last_history) = last_history {
-        if last_history.status == Status::Disabled as i32 {
+        if last_history.status == Status::Disabled {
             return Ok(last_history);
         }
         Some(last_history)
@@ -170,7 +185,7 @@ pub(crate) fn create_disabled_history_if_needed(
             group_id: group.id,
             path,
             version,
-            status: Status::Disabled as i32,
+            status: Status::Disabled,
             mtime: None,
             footprint_id: None,
             digest: None,
@@ -183,14 +198,14 @@ pub(crate) fn create_disabled_history_if_needed(
     Ok(history)
 }
 
-pub(crate) fn update_stat_with_footprint_if_needed(
+pub fn update_stat_with_footprint_if_needed(
     conn: &mut Connection,
     group: &Group,
     path: &str,
     footprint: &Footprint,
     mtime: NaiveDateTime,
     now: NaiveDateTime,
-) -> Result<Stat, Box<dyn Error>> {
+) -> DomainResult<Stat> {
     let history = create_history_with_footprint_if_needed(conn, group, path, footprint, mtime, now)?;
     let old_stat = Stats::find_by_path(conn, group.id, path)?;
     let old_stat = if let Some(old_stat) = old_stat {
@@ -231,12 +246,12 @@ pub(crate) fn update_stat_with_footprint_if_needed(
     Ok(stat)
 }
 
-pub(crate) fn update_disabled_stat_if_needed(
+pub fn update_disabled_stat_if_needed(
     conn: &mut Connection,
     group: &Group,
     path: &str,
     now: NaiveDateTime,
-) -> Result<Option<Stat>, Box<dyn Error>> {
+) -> DomainResult<Option<Stat>> {
     let history = create_disabled_history_if_needed(conn, group, path, now)?;
     let old_stat = Stats::find_by_path(conn, group.id, path)?;
     let old_stat = if let Some(old_stat) = old_stat {
@@ -269,7 +284,7 @@ pub(crate) fn update_disabled_stat_if_needed(
     Ok(Some(stat))
 }
 
-pub(crate) fn calc_fast_digest<R: Read>(r: &mut R) -> Result<i64, Box<dyn Error>> {
+pub fn calc_fast_digest<R: Read>(r: &mut R) -> DomainResult<i64> {
     let mut buf = [0u8; 8192];
     let mut fast_hasher = XxHash64::default();
     loop {
@@ -282,7 +297,7 @@ pub(crate) fn calc_fast_digest<R: Read>(r: &mut R) -> Result<i64, Box<dyn Error>
     Ok(Hasher::finish(&fast_hasher) as i64)
 }
 
-pub(crate) fn calc_digest<R: Read>(r: &mut R) -> Result<String, Box<dyn Error>> {
+pub fn calc_digest<R: Read>(r: &mut R) -> DomainResult<String> {
     let mut buf = [0u8; 8192];
     let mut hasher = Sha256::default();
     loop {
@@ -290,76 +305,247 @@ pub(crate) fn calc_digest<R: Read>(r: &mut R) -> Result<String, Box<dyn Error>>
         if n == 0 {
             break;
         }
-        hasher.write(&buf[0..n])?;
+        hasher.write_all(&buf[0..n])?;
     }
     Ok(treblo::hex::to_hex_string(hasher.finalize().as_slice()))
 }
 
-pub(crate) fn create_footprint_if_needed(
+pub fn create_footprint_if_needed(
     conn: &mut Connection,
     digest: &str,
     size: i64,
     fast_digest: i64,
     now: NaiveDateTime,
-) -> Result<Footprint, Box<dyn Error>> {
-    let footprint = Footprints::find_by_digest(conn, &digest)?;
+) -> DomainResult<Footprint> {
+    let footprint = Footprints::find_by_digest(conn, digest)?;
     Ok(if let Some(footprint) = footprint {
         footprint
     } else {
-        let footprint = Footprints::insert_and_find(
-            conn,
-            &FootprintInsertForm { digest: &digest, size, fast_digest, created_at: now },
-        )?;
+        let footprint =
+            Footprints::insert_and_find(conn, &FootprintInsertForm { digest, size, fast_digest, created_at: now })?;
         info!("footprint created: {}: {}", footprint.id, &footprint.digest);
         trace!("footprint created: {:?}", &footprint);
         footprint
     })
 }
 
-#[allow(dead_code)]
-pub(crate) fn create_content_with_bytes_if_needed(
+pub fn create_content_with_bytes_if_needed(
     conn: &mut Connection,
     bytes: &[u8],
+    encoder: &ContentEncoder,
     now: NaiveDateTime,
-) -> Result<(Content, Footprint), Box<dyn Error>> {
-    let mut slice = &bytes[..];
+) -> DomainResult<(Content, Footprint)> {
+    let mut slice = bytes;
     let digest = calc_digest(&mut slice)?;
-    let mut slice = &bytes[..];
+    let mut slice = bytes;
     let fast_digest = calc_fast_digest(&mut slice)?;
     let footprint = create_footprint_if_needed(conn, &digest, bytes.len() as i64, fast_digest, now)?;
-    let content = Contents::find_by_footprint_id(conn, footprint.id)?;
-    let content = if let Some(content) = content {
+    let content = create_content_if_needed(conn, footprint.id, bytes, None, encoder, now)?;
+    Ok((content, footprint))
+}
+
+pub fn create_content_if_needed(
+    conn: &mut Connection,
+    footprint_id: i32,
+    body: &[u8],
+    location: Option<&str>,
+    encoder: &ContentEncoder,
+    now: NaiveDateTime,
+) -> DomainResult<Content> {
+    let content = Contents::find_by_footprint_id(conn, footprint_id)?;
+    Ok(if let Some(content) = content {
         content
     } else {
+        let encoded = if location.is_none() {
+            encoder.encode(body)?
+        } else {
+            EncodedBody { codec: ContentCodec::Raw, dictionary_id: None, bytes: body.to_vec() }
+        };
         let content = Contents::insert_and_find(
             conn,
-            &ContentInsertForm { footprint_id: footprint.id, body: bytes, created_at: now },
+            &ContentInsertForm {
+                footprint_id,
+                body: &encoded.bytes,
+                codec: encoded.codec,
+                dictionary_id: encoded.dictionary_id,
+                location,
+                created_at: now,
+            },
         )?;
-        info!("content created: {}", content.id);
+        info!("content created: {}: {}", content.id, footprint_id);
         trace!("content created: {:?}", &content);
         content
+    })
+}
+
+pub fn decode_content_body(conn: &mut Connection, content: &Content) -> DomainResult<Vec<u8>> {
+    let dictionary = if let Some(dictionary_id) = content.dictionary_id {
+        let dictionary = Contents::find(conn, dictionary_id)?;
+        let dictionary = dictionary.ok_or_else(|| {
+            DomainError::internal("dictionary_id", format!("dictionary is not found: {}", dictionary_id))
+        })?;
+        Some(dictionary.body)
+    } else {
+        None
     };
-    Ok((content, footprint))
+    codec::decode(content.codec, &content.body, dictionary.as_deref())
 }
 
-#[allow(dead_code)]
-pub(crate) fn create_attr_and_stat_with_bytes_if_needed(
+pub fn find_content_encoder(conn: &mut Connection, level: i32) -> DomainResult<ContentEncoder> {
+    let dictionary = Contents::find_latest_by_codec(conn, ContentCodec::Dictionary)?
+        .map(|content| ContentDictionary { content_id: content.id, bytes: content.body });
+    Ok(ContentEncoder { level, dictionary })
+}
+
+pub fn train_content_dictionary(
+    conn: &mut Connection,
+    size: usize,
+    sample_size_max: usize,
+    now: NaiveDateTime,
+) -> DomainResult<Option<Content>> {
+    let mut samples = vec![];
+    let mut last_id = 0;
+    loop {
+        let contents = Contents::select_stored_after(conn, last_id, CONTENTS_PAGE_SIZE)?;
+        if contents.is_empty() {
+            break;
+        }
+        for content in contents.iter() {
+            last_id = content.id;
+            let body = decode_content_body(conn, content)?;
+            if !body.is_empty() && body.len() <= sample_size_max {
+                samples.push(body);
+            }
+        }
+    }
+    if samples.is_empty() {
+        return Ok(None);
+    }
+    let bytes = codec::train_dictionary(&samples, size)?;
+    let mut slice = bytes.as_slice();
+    let digest = calc_digest(&mut slice)?;
+    let mut slice = bytes.as_slice();
+    let fast_digest = calc_fast_digest(&mut slice)?;
+    let footprint = create_footprint_if_needed(conn, &digest, bytes.len() as i64, fast_digest, now)?;
+    // A trained dictionary is identified by its digest, so it must not take over a stored file with the same body.
+    let content = match Contents::find_by_footprint_id(conn, footprint.id)? {
+        Some(content) if content.codec == ContentCodec::Dictionary => content,
+        Some(content) => {
+            return Err(DomainError::conflict(
+                "dictionary",
+                format!("content already exists with codec {:?}: {}", content.codec, &digest),
+            ))
+        }
+        None => Contents::insert_and_find(
+            conn,
+            &ContentInsertForm {
+                footprint_id: footprint.id,
+                body: &bytes,
+                codec: ContentCodec::Dictionary,
+                dictionary_id: None,
+                location: None,
+                created_at: now,
+            },
+        )?,
+    };
+    info!("dictionary created: {}: {} ({} samples)", content.id, &footprint.digest, samples.len());
+    trace!("dictionary created: {:?}", &content);
+    Ok(Some(content))
+}
+
+pub fn recompress_contents(conn: &mut Connection, encoder: &ContentEncoder) -> DomainResult<(i64, i64)> {
+    let mut count = 0;
+    let mut updated_count = 0;
+    let mut last_id = 0;
+    loop {
+        let contents = Contents::select_stored_after(conn, last_id, CONTENTS_PAGE_SIZE)?;
+        if contents.is_empty() {
+            break;
+        }
+        for content in contents.iter() {
+            last_id = content.id;
+            count += 1;
+            let body = decode_content_body(conn, content)?;
+            let encoded = encoder.encode(&body)?;
+            if encoded.codec == content.codec
+                && encoded.dictionary_id == content.dictionary_id
+                && encoded.bytes.len() >= content.body.len()
+            {
+                continue;
+            }
+            Contents::update(
+                conn,
+                content.id,
+                &ContentUpdateForm {
+                    body: Some(&encoded.bytes),
+                    codec: Some(encoded.codec),
+                    dictionary_id: Some(encoded.dictionary_id),
+                    location: None,
+                },
+            )?;
+            updated_count += 1;
+            info!(
+                "content recompressed: {}: {:?}: {} -> {}",
+                content.id,
+                encoded.codec,
+                content.body.len(),
+                encoded.bytes.len()
+            );
+        }
+    }
+    Ok((count, updated_count))
+}
+
+pub type HistoryWithContent = (History, Option<Content>);
+
+pub fn find_history_with_content(
+    conn: &mut Connection,
+    group: &Group,
+    path: &str,
+    version: Option<i32>,
+) -> DomainResult<Option<HistoryWithContent>> {
+    let history = if let Some(version) = version {
+        Histories::find_by_path_and_version(conn, group.id, path, version)?
+    } else {
+        Histories::find_latest_by_path(conn, group.id, path)?
+    };
+    let history = if let Some(history) = history { history } else { return Ok(None) };
+    let content = if let Some(footprint_id) = history.footprint_id {
+        Contents::find_by_footprint_id(conn, footprint_id)?
+    } else {
+        None
+    };
+    Ok(Some((history, content)))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AttrValue<'a> {
+    pub key: &'a str,
+    pub value: &'a [u8],
+    pub value_content_type: ContentType,
+    pub value_summary: Option<&'a str>,
+}
+
+pub fn create_attr_and_stat_with_bytes_if_needed(
     conn: &mut Connection,
     workspace: &Workspace,
     target: &Footprint,
-    key: &str,
-    value: &[u8],
-    value_content_type: i32,
-    value_summary: Option<&str>,
+    attr_value: &AttrValue,
+    encoder: &ContentEncoder,
     now: NaiveDateTime,
-) -> Result<(Attr, Content, Stat), Box<dyn Error>> {
+) -> DomainResult<(Attr, Content, Stat)> {
+    let AttrValue { key, value, value_content_type, value_summary } = *attr_value;
     let group = create_attr_group_if_needed(conn, workspace, now)?;
-    let (content, footprint) = create_content_with_bytes_if_needed(conn, value, now)?;
-    let path = format!("{}/{}", footprint.digest, key);
+    let (content, footprint) = create_content_with_bytes_if_needed(conn, value, encoder, now)?;
+    let path = attr_stat_path(&target.digest, key);
     let stat = update_stat_with_footprint_if_needed(conn, &group, &path, &footprint, now, now)?;
     let attr = Attrs::find_by_target_footprint_id_and_key(conn, workspace.id, target.id, key)?;
     let attr = if let Some(attr) = attr {
-        if attr.attr_stat_id.map_or(false, |i| i == stat.id) {
+        if attr.attr_stat_id == Some(stat.id)
+            && attr.value_footprint_id == footprint.id
+            && attr.value_summary.as_deref() == value_summary
+            && attr.status == Status::Enabled
+        {
             attr
         } else {
             Attrs::update_and_find(
@@ -370,7 +556,7 @@ pub(crate) fn create_attr_and_stat_with_bytes_if_needed(
                     value_digest: Some(&footprint.digest),
                     value_content_type: Some(value_content_type),
                     value_summary: Some(value_summary),
-                    status: Some(Status::Enabled as i32),
+                    status: Some(Status::Enabled),
                     attr_stat_id: Some(Some(stat.id)),
                     updated_at: Some(now),
                 },
@@ -388,7 +574,7 @@ pub(crate) fn create_attr_and_stat_with_bytes_if_needed(
                 value_digest: &footprint.digest,
                 value_content_type,
                 value_summary,
-                status: Status::Enabled as i32,
+                status: Status::Enabled,
                 attr_stat_id: Some(stat.id),
                 created_at: now,
                 updated_at: now,
@@ -398,26 +584,83 @@ pub(crate) fn create_attr_and_stat_with_bytes_if_needed(
     Ok((attr, content, stat))
 }
 
+fn attr_stat_path(target_digest: &str, key: &str) -> String {
+    format!("{}/{}", target_digest, key)
+}
+
+pub fn disable_attr_and_stat_if_needed(
+    conn: &mut Connection,
+    workspace: &Workspace,
+    target: &Footprint,
+    key: &str,
+    now: NaiveDateTime,
+) -> DomainResult<Option<Attr>> {
+    let attr = Attrs::find_by_target_footprint_id_and_key(conn, workspace.id, target.id, key)?;
+    let attr = match attr {
+        Some(attr) if attr.status == Status::Enabled => attr,
+        _ => return Ok(None),
+    };
+    let group = create_attr_group_if_needed(conn, workspace, now)?;
+    let path = attr_stat_path(&target.digest, key);
+    let stat = update_disabled_stat_if_needed(conn, &group, &path, now)?;
+    let attr = Attrs::update_and_find(
+        conn,
+        attr.id,
+        &AttrUpdateForm {
+            status: Some(Status::Disabled),
+            attr_stat_id: Some(stat.map(|s| s.id)),
+            updated_at: Some(now),
+            ..Default::default()
+        },
+    )?;
+    info!("attr disabled: {}: {}", attr.id, &path);
+    trace!("attr disabled: {:?}", &attr);
+    Ok(Some(attr))
+}
+
+pub fn select_attr_histories(
+    conn: &mut Connection,
+    workspace: &Workspace,
+    target: &Footprint,
+    key: &str,
+) -> DomainResult<Vec<History>> {
+    let group = Groups::find_by_name(conn, workspace.id, ATTR_GROUP_NAME)?;
+    let group = if let Some(group) = group { group } else { return Ok(vec![]) };
+    let mut histories = Histories::select_by_path(conn, group.id, &attr_stat_path(&target.digest, key))?;
+    histories.sort_by_key(|h| h.version);
+    Ok(histories)
+}
+
+pub fn find_attr_history_at(
+    conn: &mut Connection,
+    workspace: &Workspace,
+    target: &Footprint,
+    key: &str,
+    at: NaiveDateTime,
+) -> DomainResult<Option<History>> {
+    let group = Groups::find_by_name(conn, workspace.id, ATTR_GROUP_NAME)?;
+    let group = if let Some(group) = group { group } else { return Ok(None) };
+    let history = Histories::find_latest_by_path_at(conn, group.id, &attr_stat_path(&target.digest, key), at)?;
+    Ok(history.filter(|h| h.status == Status::Enabled))
+}
+
 // file
 
 #[derive(Debug)]
-pub(crate) enum FileState {
+pub enum FileState {
     Enabled(FileMetadata),
     Disabled,
 }
 
 #[derive(Debug)]
-pub(crate) struct FileMetadata {
+pub struct FileMetadata {
     pub size: i64,
     pub mtime: NaiveDateTime,
     pub fast_digest: i64,
     pub digest: String,
 }
 
-pub(crate) fn new_updated_file_state_if_needed(
-    stat: Option<&Stat>,
-    path: &Path,
-) -> Result<Option<FileState>, Box<dyn Error>> {
+pub fn new_updated_file_state_if_needed(stat: Option<&Stat>, path: &Path) -> DomainResult<Option<FileState>> {
     let (f, mtime, size) = if let Ok(f) = File::open(path) {
         let md = f.metadata()?;
         let mtime = DateTime::<Utc>::from(md.modified()?).naive_utc();
@@ -426,7 +669,7 @@ pub(crate) fn new_updated_file_state_if_needed(
     } else {
         (None, None, None)
     };
-    let not_exists = stat.map_or(false, |s| s.status == Status::Disabled as i32);
+    let not_exists = stat.is_some_and(|s| s.status == Status::Disabled);
     if f.is_none() && not_exists {
         return Ok(None);
     }
@@ -457,13 +700,13 @@ pub(crate) fn new_updated_file_state_if_needed(
     }
 }
 
-pub(crate) fn update_stat_with_paths_if_needed(
+pub fn update_stat_with_paths_if_needed(
     conn: &mut Connection,
     group: &Group,
     stat_path: &str,
     file_path: &Path,
     now: NaiveDateTime,
-) -> Result<Option<Stat>, Box<dyn Error>> {
+) -> DomainResult<Option<Stat>> {
     let old_stat = Stats::find_by_path(conn, group.id, stat_path)?;
     let file_state = new_updated_file_state_if_needed(old_stat.as_ref(), file_path)?;
     trace!("updated file state: {:?}", file_state);
@@ -481,25 +724,25 @@ pub(crate) fn update_stat_with_paths_if_needed(
     }
 }
 
-pub(crate) fn update_stat_with_present_paths_if_needed(
+pub fn update_stat_with_present_paths_if_needed(
     conn: &mut Connection,
     group: &Group,
     stat_path: &str,
     file_path: &Path,
     now: NaiveDateTime,
-) -> Result<Stat, Box<dyn Error>> {
+) -> DomainResult<Stat> {
     update_stat_with_paths_if_needed(conn, group, stat_path, file_path, now).map(|s| s.unwrap())
 }
 
-pub(crate) fn update_meta_group_stat(
+pub fn update_meta_group_stat(
     conn: &mut Connection,
     workspace: &Workspace,
     group: &Group,
     db_path: &Path,
     now: NaiveDateTime,
-) -> Result<Group, Box<dyn Error>> {
+) -> DomainResult<Group> {
     let stat_path = &group.name;
-    let meta_group = create_meta_group_if_needed(conn, &workspace, now)?;
+    let meta_group = create_meta_group_if_needed(conn, workspace, now)?;
     let stat = update_stat_with_present_paths_if_needed(conn, &meta_group, stat_path, db_path, now)?;
     let group = Groups::update_and_find(
         conn,
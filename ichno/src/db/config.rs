@@ -1,4 +1,93 @@
-use diesel::{sqlite::Sqlite, SqliteConnection};
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+use diesel::connection::SimpleConnection;
+#[cfg(feature = "mysql")]
+use diesel::MysqlConnection;
+#[cfg(feature = "postgres")]
+use diesel::PgConnection;
+use diesel::{
+    r2d2::{self, R2D2Connection},
+    Connection as _, SqliteConnection,
+};
 
-pub type Connection = SqliteConnection;
-pub type Backend = Sqlite;
+use crate::{
+    db::util::lower,
+    error::{DomainError, DomainResult},
+};
+
+mod multi {
+    #[cfg(feature = "mysql")]
+    use diesel::MysqlConnection;
+    #[cfg(feature = "postgres")]
+    use diesel::PgConnection;
+    use diesel::SqliteConnection;
+
+    #[derive(diesel::MultiConnection)]
+    pub enum AnyConnection {
+        #[cfg(feature = "postgres")]
+        Pg(PgConnection),
+        #[cfg(feature = "mysql")]
+        Mysql(MysqlConnection),
+        Sqlite(SqliteConnection),
+    }
+}
+
+pub use multi::{AnyConnection as Connection, MultiBackend as Backend};
+
+pub fn establish(database_url: &str) -> DomainResult<Connection> {
+    let scheme = database_url.split_once("://").map(|(scheme, _)| scheme);
+    match scheme {
+        #[cfg(feature = "postgres")]
+        Some("postgres" | "postgresql") => {
+            let mut conn = PgConnection::establish(database_url)?;
+            conn.batch_execute("SET TIME ZONE 'UTC'")?;
+            Ok(Connection::Pg(conn))
+        }
+        #[cfg(feature = "mysql")]
+        Some("mysql") => {
+            let mut conn = MysqlConnection::establish(database_url)?;
+            conn.batch_execute("SET time_zone = '+00:00'")?;
+            Ok(Connection::Mysql(conn))
+        }
+        Some("sqlite") => establish_sqlite(&database_url["sqlite://".len()..]),
+        None => establish_sqlite(database_url),
+        Some(scheme) => {
+            Err(DomainError::unsupported_scheme("database_url", format!("unsupported database scheme: {}", scheme)))
+        }
+    }
+}
+
+fn establish_sqlite(path: &str) -> DomainResult<Connection> {
+    let mut conn = SqliteConnection::establish(path)?;
+    // The built-in lower() only folds ASCII, unlike the ones of the other backends.
+    lower::register_impl(&mut conn, |s: String| s.to_lowercase())?;
+    Ok(Connection::Sqlite(conn))
+}
+
+#[derive(Debug, Clone)]
+pub struct ConnectionManager {
+    database_url: String,
+}
+
+impl ConnectionManager {
+    pub fn new(database_url: impl Into<String>) -> ConnectionManager {
+        ConnectionManager { database_url: database_url.into() }
+    }
+}
+
+impl r2d2::ManageConnection for ConnectionManager {
+    type Connection = Connection;
+    type Error = r2d2::Error;
+
+    fn connect(&self) -> Result<Connection, r2d2::Error> {
+        establish(&self.database_url)
+            .map_err(|e| r2d2::Error::ConnectionError(diesel::ConnectionError::BadConnection(e.to_string())))
+    }
+
+    fn is_valid(&self, conn: &mut Connection) -> Result<(), r2d2::Error> {
+        conn.ping().map_err(r2d2::Error::QueryError)
+    }
+
+    fn has_broken(&self, conn: &mut Connection) -> bool {
+        std::thread::panicking() || conn.is_broken()
+    }
+}
@@ -1,15 +1,15 @@
 #[macro_export]
 macro_rules! impl_find {
     ( $conn: ty, $table: ident, $t: ty ) => {
-        pub fn find(conn: &mut $conn, id: i32) -> Result<::std::option::Option<$t>, Box<dyn ::std::error::Error>> {
-            use crate::db::schema::$table::dsl;
+        pub fn find(conn: &mut $conn, id: i32) -> $crate::error::DomainResult<::std::option::Option<$t>> {
+            use $crate::db::schema::$table::dsl;
             let q = dsl::$table.find(id);
             Ok(q.first::<$t>(conn).optional()?)
         }
     };
     ( $conn: ty, $table: ident, $t: ty; $n: ident, $( $arg: ident : $arg_t: ty ),+ ) => {
-        pub fn $n(conn: &mut $conn, $($arg: $arg_t ,)+) -> Result<::std::option::Option<$t>, Box<dyn ::std::error::Error>> {
-            use crate::db::schema::$table::dsl;
+        pub fn $n(conn: &mut $conn, $($arg: $arg_t ,)+) -> $crate::error::DomainResult<::std::option::Option<$t>> {
+            use $crate::db::schema::$table::dsl;
             let q = dsl::$table
             $(
                 .filter(dsl::$arg.eq($arg))
@@ -23,15 +23,15 @@ macro_rules! impl_find {
 #[macro_export]
 macro_rules! impl_select {
     ( $conn: ty, $table: ident, $t: ty ) => {
-        pub fn select(conn: &mut $conn, ids: &Vec<i32>) -> Result<::std::vec::Vec<$t>, Box<dyn Error>> {
-            use crate::db::schema::$table::dsl;
+        pub fn select(conn: &mut $conn, ids: &Vec<i32>) -> $crate::error::DomainResult<::std::vec::Vec<$t>> {
+            use $crate::db::schema::$table::dsl;
             let q = dsl::$table.filter(dsl::id.eq_any(ids));
             Ok(q.load::<$t>(conn)?)
         }
     };
     ( $conn: ty, $table: ident, $t: ty; $n: ident, $( $arg: ident : $arg_t: ty ),+ ) => {
-        pub fn $n(conn: &mut $conn, $($arg: $arg_t ,)+) -> Result<::std::vec::Vec<$t>, Box<dyn Error>> {
-            use crate::db::schema::$table::dsl;
+        pub fn $n(conn: &mut $conn, $($arg: $arg_t ,)+) -> $crate::error::DomainResult<::std::vec::Vec<$t>> {
+            use $crate::db::schema::$table::dsl;
             let q = dsl::$table
             $(
                 .filter(dsl::$arg.eq($arg))
@@ -45,8 +45,8 @@ macro_rules! impl_select {
 #[macro_export]
 macro_rules! impl_insert {
     ( $conn: ty, $table: ident, $t: ty ) => {
-        pub fn insert(conn: &mut $conn, insert_form: &$t) -> Result<(), Box<dyn Error>> {
-            use crate::db::schema::$table::dsl;
+        pub fn insert(conn: &mut $conn, insert_form: &$t) -> $crate::error::DomainResult<()> {
+            use $crate::db::schema::$table::dsl;
             let q = ::diesel::insert_into(dsl::$table).values(insert_form);
             q.execute(conn)?;
             Ok(())
@@ -54,10 +54,30 @@ macro_rules! impl_insert {
     };
 }
 
+#[macro_export]
+macro_rules! impl_insert_all {
+    ( $conn: ty, $table: ident, $t: ty ) => {
+        pub fn insert_all(conn: &mut $conn, insert_forms: &[$t]) -> $crate::error::DomainResult<usize> {
+            use $crate::db::schema::$table::dsl;
+            if insert_forms.is_empty() {
+                return Ok(0);
+            }
+            let q = ::diesel::insert_into(dsl::$table).values(insert_forms);
+            Ok(match conn {
+                #[cfg(feature = "postgres")]
+                $crate::db::Connection::Pg(conn) => q.execute(conn)?,
+                #[cfg(feature = "mysql")]
+                $crate::db::Connection::Mysql(conn) => q.execute(conn)?,
+                $crate::db::Connection::Sqlite(conn) => q.execute(conn)?,
+            })
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! impl_insert_and_find {
     ( $conn: ty, $table: ident, $t: ty, $r: ty; $n: ident, $( $arg: ident ),+ ) => {
-        pub fn insert_and_find(conn: &mut $conn, insert_form: &$t) -> Result<$r, Box<dyn Error>> {
+        pub fn insert_and_find(conn: &mut $conn, insert_form: &$t) -> $crate::error::DomainResult<$r> {
             Self::insert(conn, insert_form)?;
             let inserted = Self::$n(conn, $(insert_form.$arg),+)?;
             Ok(inserted.unwrap())
@@ -71,8 +91,8 @@ macro_rules! impl_insert_and_find {
 #[macro_export]
 macro_rules! impl_update {
     ( $conn: ty, $table: ident, $t: ty ) => {
-        pub fn update(conn: &mut $conn, id: i32, update_form: &$t) -> Result<(), Box<dyn Error>> {
-            use crate::db::schema::$table::dsl;
+        pub fn update(conn: &mut $conn, id: i32, update_form: &$t) -> $crate::error::DomainResult<()> {
+            use $crate::db::schema::$table::dsl;
             let q = ::diesel::update(dsl::$table.find(id)).set(update_form);
             let n = q.execute(conn)?;
             assert_eq!(1, n);
@@ -84,7 +104,7 @@ macro_rules! impl_update {
 #[macro_export]
 macro_rules! impl_update_and_find {
     ( $conn: ty, $table: ident, $t: ty, $r: ty ) => {
-        pub fn update_and_find(conn: &mut $conn, id: i32, update_form: &$t) -> Result<$r, Box<dyn Error>> {
+        pub fn update_and_find(conn: &mut $conn, id: i32, update_form: &$t) -> $crate::error::DomainResult<$r> {
             Self::update(conn, id, update_form)?;
             let updated = Self::find(conn, id)?;
             Ok(updated.unwrap())
@@ -109,6 +129,7 @@ macro_rules! impl_crud {
     ( $conn: ty, $table: ident, $t: ty, $i: ty ) => {
         $crate::impl_crud!($conn, $table, $t);
         $crate::impl_insert!($conn, $table, $i);
+        $crate::impl_insert_all!($conn, $table, $i);
     };
     ( $conn: ty, $table: ident, $t: ty, $i: ty; $n: ident, $( $arg: ident : $arg_t: ty ),+ ) => {
         $crate::impl_crud!($conn, $table, $t, $i);
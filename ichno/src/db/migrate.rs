@@ -1,12 +1,19 @@
-use std::error::Error;
-
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 
-use crate::db::config::Connection;
+use crate::{
+    db::config::Connection,
+    error::{DomainError, DomainResult},
+};
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
 
-pub fn migrate(conn: &mut Connection) -> Result<(), Box<dyn Error>> {
-    conn.run_pending_migrations(MIGRATIONS).unwrap();
-    Ok(())
+pub fn migrate(conn: &mut Connection) -> DomainResult<()> {
+    match conn {
+        Connection::Sqlite(conn) => {
+            conn.run_pending_migrations(MIGRATIONS)?;
+            Ok(())
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(DomainError::params("connection", "ichno databases must be SQLite".to_owned())),
+    }
 }
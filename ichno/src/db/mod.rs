@@ -8,8 +8,9 @@ mod migrate;
 
 pub mod actions;
 
-pub use migrate::migrate;
+pub use config::{establish, Backend, Connection, ConnectionManager};
+pub use migrate::{migrate, MIGRATIONS};
 pub use util::{
-    Attrs as SqliteAttrs, Contents as SqliteContents, Footprints as SqliteFootprints, Groups as SqliteGroups,
-    Histories as SqliteHistories, StatOrder, StatSearchCondition, Stats as SqliteStats, Workspaces as SqliteWorkspaces,
+    tokenize_path, AttrCursor, Attrs, Contents, Footprints, Groups, Histories, HistoryCursor, HistorySearchCondition,
+    SnapshotCondition, SnapshotCursor, StatCursor, StatOrder, StatSearchCondition, Stats, Workspaces,
 };
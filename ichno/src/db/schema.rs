@@ -21,6 +21,9 @@ table! {
         id -> Integer,
         footprint_id -> Integer,
         body -> Binary,
+        codec -> Integer,
+        dictionary_id -> Nullable<Integer>,
+        location -> Nullable<Text>,
         created_at -> Timestamp,
     }
 }
@@ -46,6 +49,10 @@ table! {
         description -> Text,
         status -> Integer,
         group_stat_id -> Nullable<Integer>,
+        pulled_history_id -> Nullable<Integer>,
+        pulled_etag -> Nullable<Text>,
+        pulled_last_modified -> Nullable<Text>,
+        pulled_source_id -> Nullable<Text>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
     }
@@ -1,19 +1,60 @@
-use std::error::Error;
+use std::collections::HashMap;
 
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use chrono::NaiveDateTime;
-use diesel::prelude::*;
+use diesel::{
+    prelude::*,
+    sql_query,
+    sql_types::{BigInt, Bool, Double, Integer, Nullable, Text},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
     db::config::{Backend, Connection},
+    error::{DomainError, DomainResult},
     impl_crud, impl_select,
     models::{
-        Attr, AttrInsertForm, AttrUpdateForm, Content, ContentInsertForm, Footprint, FootprintInsertForm, Group,
-        GroupInsertForm, GroupUpdateForm, History, HistoryInsertForm, Stat, StatInsertForm, StatUpdateForm, Workspace,
-        WorkspaceInsertForm, WorkspaceUpdateForm,
+        Attr, AttrInsertForm, AttrUpdateForm, Content, ContentInsertForm, ContentUpdateForm, Footprint,
+        FootprintInsertForm, Group, GroupInsertForm, GroupUpdateForm, History, HistoryInsertForm, Stat, StatInsertForm,
+        StatUpdateForm, Workspace, WorkspaceInsertForm, WorkspaceUpdateForm,
     },
-    Status,
+    ContentCodec, GroupType, Status,
 };
 
+sql_function!(fn coalesce(x: Nullable<BigInt>, y: BigInt) -> BigInt);
+sql_function!(fn lower(x: Text) -> Text);
+
+const LIKE_ESCAPE: char = '!';
+
+fn escape_like(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c == '%' || c == '_' || c == LIKE_ESCAPE {
+            escaped.push(LIKE_ESCAPE);
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn glob_to_like(glob: &str) -> String {
+    let mut pattern = String::with_capacity(glob.len());
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => {
+                while chars.peek() == Some(&'*') {
+                    chars.next();
+                }
+                pattern.push('%');
+            }
+            '?' => pattern.push('_'),
+            c => pattern.push_str(&escape_like(&c.to_string())),
+        }
+    }
+    pattern
+}
+
 pub struct Footprints;
 
 impl Footprints {
@@ -21,15 +62,57 @@ impl Footprints {
         Connection, footprints, Footprint, FootprintInsertForm;
         find_by_digest, digest: &str
     );
+
+    pub fn select_by_digests(conn: &mut Connection, digests: &[&str]) -> DomainResult<Vec<Footprint>> {
+        use crate::db::schema::footprints::dsl;
+        let q = dsl::footprints.filter(dsl::digest.eq_any(digests));
+        Ok(q.load::<Footprint>(conn)?)
+    }
+
+    pub fn select_by_digest_prefix(
+        conn: &mut Connection,
+        digest_prefix: &str,
+        limit: i64,
+    ) -> DomainResult<Vec<Footprint>> {
+        use crate::db::schema::footprints::dsl;
+        let q = dsl::footprints
+            .filter(dsl::digest.like(format!("{}%", escape_like(digest_prefix))).escape(LIKE_ESCAPE))
+            .order(dsl::digest.asc())
+            .limit(limit);
+        Ok(q.load::<Footprint>(conn)?)
+    }
 }
 
 pub struct Contents;
 
 impl Contents {
     impl_crud!(
-        Connection, contents, Content, ContentInsertForm;
+        Connection, contents, Content, ContentInsertForm, ContentUpdateForm;
         find_by_footprint_id, footprint_id: i32
     );
+
+    pub fn select_by_footprint_ids(conn: &mut Connection, footprint_ids: &[i32]) -> DomainResult<Vec<Content>> {
+        use crate::db::schema::contents::dsl;
+        let q = dsl::contents.filter(dsl::footprint_id.eq_any(footprint_ids));
+        Ok(q.load::<Content>(conn)?)
+    }
+
+    pub fn find_latest_by_codec(conn: &mut Connection, codec: ContentCodec) -> DomainResult<Option<Content>> {
+        use crate::db::schema::contents::dsl;
+        let q = dsl::contents.filter(dsl::codec.eq(codec)).order(dsl::id.desc()).limit(1);
+        Ok(q.first::<Content>(conn).optional()?)
+    }
+
+    pub fn select_stored_after(conn: &mut Connection, id: i32, limit: i64) -> DomainResult<Vec<Content>> {
+        use crate::db::schema::contents::dsl;
+        let q = dsl::contents
+            .filter(dsl::id.gt(id))
+            .filter(dsl::location.is_null())
+            .filter(dsl::codec.ne(ContentCodec::Dictionary))
+            .order(dsl::id.asc())
+            .limit(limit);
+        Ok(q.load::<Content>(conn)?)
+    }
 }
 
 pub struct Workspaces;
@@ -64,19 +147,121 @@ impl Histories {
 
     impl_select!(Connection, histories, History; select_by_footprint_id, workspace_id: i32, footprint_id: i32);
 
-    pub fn find_latest_by_path(
+    pub fn find_latest_by_path(conn: &mut Connection, group_id: i32, path: &str) -> DomainResult<Option<History>> {
+        use crate::db::schema::histories::dsl;
+        let q = dsl::histories
+            .filter(dsl::group_id.eq(group_id))
+            .filter(dsl::path.eq(path))
+            .order(dsl::version.desc())
+            .limit(1);
+        Ok(q.first::<History>(conn).optional()?)
+    }
+
+    pub fn select_by_paths(conn: &mut Connection, group_id: i32, paths: &[&str]) -> DomainResult<Vec<History>> {
+        use crate::db::schema::histories::dsl;
+        let q = dsl::histories.filter(dsl::group_id.eq(group_id)).filter(dsl::path.eq_any(paths));
+        Ok(q.load::<History>(conn)?)
+    }
+
+    pub fn find_last(conn: &mut Connection) -> DomainResult<Option<History>> {
+        use crate::db::schema::histories::dsl;
+        let q = dsl::histories.order(dsl::id.desc()).limit(1);
+        Ok(q.first::<History>(conn).optional()?)
+    }
+
+    pub fn select_by_group_ids_after(
+        conn: &mut Connection,
+        group_ids: &[i32],
+        id: i32,
+        limit: i64,
+    ) -> DomainResult<Vec<History>> {
+        use crate::db::schema::histories::dsl;
+        let q = dsl::histories
+            .filter(dsl::group_id.eq_any(group_ids))
+            .filter(dsl::id.gt(id))
+            .order(dsl::id.asc())
+            .limit(limit);
+        Ok(q.load::<History>(conn)?)
+    }
+
+    pub fn search(
+        conn: &mut Connection,
+        workspace_id: i32,
+        cond: &HistorySearchCondition,
+    ) -> DomainResult<Vec<History>> {
+        use crate::db::schema::histories::dsl;
+        let mut q = dsl::histories.into_boxed();
+        q = q.filter(dsl::workspace_id.eq(workspace_id));
+        if let Some(ref group_ids) = cond.group_ids {
+            q = q.filter(dsl::group_id.eq_any(group_ids));
+        }
+        if let Some(path_prefix) = cond.path_prefix {
+            q = q.filter(dsl::path.like(format!("{}%", escape_like(path_prefix))).escape(LIKE_ESCAPE));
+        }
+        if let Some(footprint_id) = cond.footprint_id {
+            q = q.filter(dsl::footprint_id.eq(footprint_id));
+        }
+        if let Some(ref after) = cond.after {
+            q = q.filter(dsl::id.lt(after.id));
+        }
+        q = q.order(dsl::id.desc());
+        let limit = cond.limit.unwrap_or(-1);
+        if limit >= 0 {
+            q = q.limit(limit);
+        }
+        Ok(q.load::<History>(conn)?)
+    }
+
+    pub fn find_latest_by_path_at(
         conn: &mut Connection,
         group_id: i32,
         path: &str,
-    ) -> Result<Option<History>, Box<dyn Error>> {
+        at: NaiveDateTime,
+    ) -> DomainResult<Option<History>> {
         use crate::db::schema::histories::dsl;
         let q = dsl::histories
             .filter(dsl::group_id.eq(group_id))
             .filter(dsl::path.eq(path))
+            .filter(dsl::created_at.le(at))
             .order(dsl::version.desc())
             .limit(1);
         Ok(q.first::<History>(conn).optional()?)
     }
+
+    pub fn snapshot(
+        conn: &mut Connection,
+        group_id: i32,
+        as_of: NaiveDateTime,
+        cond: &SnapshotCondition,
+    ) -> DomainResult<Vec<History>> {
+        use crate::db::schema::histories::dsl;
+        let newer = diesel::alias!(crate::db::schema::histories as newer);
+        let newer_exists = diesel::dsl::exists(
+            newer
+                .filter(newer.field(dsl::group_id).eq(dsl::group_id))
+                .filter(newer.field(dsl::path).eq(dsl::path))
+                .filter(newer.field(dsl::version).gt(dsl::version))
+                .filter(newer.field(dsl::created_at).le(as_of)),
+        );
+        let mut q = dsl::histories
+            .filter(dsl::group_id.eq(group_id))
+            .filter(dsl::created_at.le(as_of))
+            .filter(dsl::status.eq(Status::Enabled))
+            .filter(diesel::dsl::not(newer_exists))
+            .into_boxed();
+        if let Some(path_prefix) = cond.path_prefix {
+            q = q.filter(dsl::path.like(format!("{}%", escape_like(path_prefix))).escape(LIKE_ESCAPE));
+        }
+        if let Some(ref after) = cond.after {
+            q = q.filter(dsl::path.gt(after.path.clone()));
+        }
+        q = q.order(dsl::path.asc());
+        let limit = cond.limit.unwrap_or(-1);
+        if limit >= 0 {
+            q = q.limit(limit);
+        }
+        Ok(q.load::<History>(conn)?)
+    }
 }
 
 pub struct Stats;
@@ -91,7 +276,17 @@ impl Stats {
 
     impl_select!(Connection, stats, Stat; select_by_footprint_id, workspace_id: i32, footprint_id: i32);
 
-    fn search_condition_to_query<'a>(
+    pub fn select_by_footprint_ids(
+        conn: &mut Connection,
+        workspace_id: i32,
+        footprint_ids: &[i32],
+    ) -> DomainResult<Vec<Stat>> {
+        use crate::db::schema::stats::dsl;
+        let q = dsl::stats.filter(dsl::workspace_id.eq(workspace_id)).filter(dsl::footprint_id.eq_any(footprint_ids));
+        Ok(q.load::<Stat>(conn)?)
+    }
+
+    fn search_condition_to_filter<'a>(
         workspace_id: i32,
         cond: &'a StatSearchCondition,
     ) -> crate::db::schema::stats::BoxedQuery<'a, Backend> {
@@ -105,15 +300,34 @@ impl Stats {
             q = q.filter(dsl::path.eq_any(paths));
         }
         if let Some(path_prefix) = cond.path_prefix {
-            q = q.filter(dsl::path.like(format!("{}%", path_prefix)));
+            q = q.filter(dsl::path.like(format!("{}%", escape_like(path_prefix))).escape(LIKE_ESCAPE));
         }
         if let Some(path_partial) = cond.path_partial {
             if path_partial.len() >= 2 {
-                q = q.filter(dsl::path.like(format!("%{}%", path_partial)));
+                q = q.filter(dsl::path.like(format!("%{}%", escape_like(path_partial))).escape(LIKE_ESCAPE));
             }
         }
+        if let Some(path_glob) = cond.path_glob {
+            q = q.filter(lower(dsl::path).like(glob_to_like(&path_glob.to_lowercase())).escape(LIKE_ESCAPE));
+        }
+        if let Some(ref extensions) = cond.extensions {
+            let filter = extensions
+                .iter()
+                .map(|extension| {
+                    let pattern = format!("%.{}", escape_like(&extension.trim_start_matches('.').to_lowercase()));
+                    Box::new(lower(dsl::path).like(pattern).escape(LIKE_ESCAPE))
+                        as Box<dyn BoxableExpression<dsl::stats, Backend, SqlType = Bool>>
+                })
+                .reduce(|filter, like| Box::new(filter.or(like)));
+            if let Some(filter) = filter {
+                q = q.filter(filter);
+            }
+        }
+        if let Some(digest_prefix) = cond.digest_prefix {
+            q = q.filter(dsl::digest.like(format!("{}%", escape_like(digest_prefix))).escape(LIKE_ESCAPE));
+        }
         if let Some(ref statuses) = cond.statuses {
-            q = q.filter(dsl::status.eq_any(statuses.iter().map(|s| *s as i32)));
+            q = q.filter(dsl::status.eq_any(statuses));
         }
         if let Some(mtime) = cond.mtime_after {
             q = q.filter(dsl::mtime.ge(mtime));
@@ -133,35 +347,150 @@ impl Stats {
         if let Some(updated_at) = cond.updated_at_before {
             q = q.filter(dsl::updated_at.lt(updated_at));
         }
-        if let Some(ref order) = cond.order {
-            q = match order {
-                StatOrder::PathAsc => q.order(dsl::path.asc()),
-                StatOrder::PathDesc => q.order(dsl::path.desc()),
-                StatOrder::UpdatedAtAsc => q.order(dsl::updated_at.asc()),
-                StatOrder::UpdatedAtDesc => q.order(dsl::updated_at.desc()),
+        if let Some(ref after) = cond.after {
+            let (path, updated_at, size, id) =
+                (after.path.clone(), after.updated_at, after.size.unwrap_or(-1), after.id);
+            q = match cond.order.clone().unwrap_or_default() {
+                StatOrder::PathAsc => q.filter(dsl::path.gt(path.clone()).or(dsl::path.eq(path).and(dsl::id.gt(id)))),
+                StatOrder::PathDesc => q.filter(dsl::path.lt(path.clone()).or(dsl::path.eq(path).and(dsl::id.lt(id)))),
+                StatOrder::UpdatedAtAsc => {
+                    q.filter(dsl::updated_at.gt(updated_at).or(dsl::updated_at.eq(updated_at).and(dsl::id.gt(id))))
+                }
+                StatOrder::UpdatedAtDesc => {
+                    q.filter(dsl::updated_at.lt(updated_at).or(dsl::updated_at.eq(updated_at).and(dsl::id.lt(id))))
+                }
+                StatOrder::SizeAsc => {
+                    q.filter(coalesce(dsl::size, -1).gt(size).or(coalesce(dsl::size, -1).eq(size).and(dsl::id.gt(id))))
+                }
+                StatOrder::SizeDesc => {
+                    q.filter(coalesce(dsl::size, -1).lt(size).or(coalesce(dsl::size, -1).eq(size).and(dsl::id.lt(id))))
+                }
             }
         }
+        q
+    }
+
+    fn search_condition_to_query<'a>(
+        workspace_id: i32,
+        cond: &'a StatSearchCondition,
+    ) -> crate::db::schema::stats::BoxedQuery<'a, Backend> {
+        use crate::db::schema::stats::dsl;
+        let mut q = Stats::search_condition_to_filter(workspace_id, cond);
+        // Always order by the cursor key, so that the first page is consistent with the following ones.
+        q = match cond.order.clone().unwrap_or_default() {
+            StatOrder::PathAsc => q.order((dsl::path.asc(), dsl::id.asc())),
+            StatOrder::PathDesc => q.order((dsl::path.desc(), dsl::id.desc())),
+            StatOrder::UpdatedAtAsc => q.order((dsl::updated_at.asc(), dsl::id.asc())),
+            StatOrder::UpdatedAtDesc => q.order((dsl::updated_at.desc(), dsl::id.desc())),
+            StatOrder::SizeAsc => q.order((coalesce(dsl::size, -1).asc(), dsl::id.asc())),
+            StatOrder::SizeDesc => q.order((coalesce(dsl::size, -1).desc(), dsl::id.desc())),
+        };
         let limit = cond.limit.unwrap_or(-1);
         if limit >= 0 {
             q = q.limit(limit);
         }
-        return q;
+        q
     }
 
-    pub fn count(conn: &mut Connection, workspace_id: i32, cond: &StatSearchCondition) -> Result<i64, Box<dyn Error>> {
-        let cond = StatSearchCondition { limit: Some(-1), ..cond.clone() };
-        let q = Stats::search_condition_to_query(workspace_id, &cond);
+    pub fn count(conn: &mut Connection, workspace_id: i32, cond: &StatSearchCondition) -> DomainResult<i64> {
+        let q = Stats::search_condition_to_filter(workspace_id, cond);
         Ok(q.count().first(conn)?)
     }
 
-    pub fn search(
-        conn: &mut Connection,
-        workspace_id: i32,
-        cond: &StatSearchCondition,
-    ) -> Result<Vec<Stat>, Box<dyn Error>> {
+    pub fn search(conn: &mut Connection, workspace_id: i32, cond: &StatSearchCondition) -> DomainResult<Vec<Stat>> {
         let q = Stats::search_condition_to_query(workspace_id, cond);
         Ok(q.load::<Stat>(conn)?)
     }
+
+    /// On SQLite, this needs the `stats_fts` index created by the server migrations of ichnome.
+    pub fn search_by_path_tokens(
+        conn: &mut Connection,
+        workspace_id: i32,
+        tokens: &[&str],
+        limit: i64,
+    ) -> DomainResult<Vec<(Stat, f64)>> {
+        if tokens.is_empty() {
+            return Ok(vec![]);
+        }
+        let hits: Vec<PathSearchHit> = match conn {
+            #[cfg(feature = "postgres")]
+            Connection::Pg(conn) => {
+                let patterns: Vec<String> = tokens
+                    .iter()
+                    .map(|t| format!("%{}%", t.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")))
+                    .collect();
+                sql_query(
+                    "SELECT s.id AS id, word_similarity($1, s.path)::float8 AS score \
+                     FROM stats s JOIN groups g ON g.id = s.group_id \
+                     WHERE s.path ILIKE ALL($2) AND s.workspace_id = $3 AND s.status = $4 AND g.type IN ($5, $6, $7) \
+                     ORDER BY score DESC, s.id ASC LIMIT $8",
+                )
+                .bind::<Text, _>(tokens.join(" "))
+                .bind::<diesel::sql_types::Array<Text>, _>(patterns)
+                .bind::<Integer, _>(workspace_id)
+                .bind::<Integer, _>(Status::Enabled)
+                .bind::<Integer, _>(GroupType::Local)
+                .bind::<Integer, _>(GroupType::Remote)
+                .bind::<Integer, _>(GroupType::Pushed)
+                .bind::<BigInt, _>(limit)
+                .load(conn)?
+            }
+            #[cfg(feature = "mysql")]
+            Connection::Mysql(conn) => {
+                let query: Vec<String> = tokens.iter().map(|t| format!("+\"{}\"", t.replace('"', ""))).collect();
+                let query = query.join(" ");
+                sql_query(
+                    "SELECT s.`id` AS `id`, MATCH (s.`path`) AGAINST (? IN BOOLEAN MODE) AS `score` \
+                     FROM `stats` s JOIN `groups` g ON g.`id` = s.`group_id` \
+                     WHERE MATCH (s.`path`) AGAINST (? IN BOOLEAN MODE) AND s.`workspace_id` = ? AND s.`status` = ? \
+                     AND g.`type` IN (?, ?, ?) \
+                     ORDER BY `score` DESC, s.`id` ASC LIMIT ?",
+                )
+                .bind::<Text, _>(&query)
+                .bind::<Text, _>(&query)
+                .bind::<Integer, _>(workspace_id)
+                .bind::<Integer, _>(Status::Enabled)
+                .bind::<Integer, _>(GroupType::Local)
+                .bind::<Integer, _>(GroupType::Remote)
+                .bind::<Integer, _>(GroupType::Pushed)
+                .bind::<BigInt, _>(limit)
+                .load(conn)?
+            }
+            Connection::Sqlite(conn) => {
+                let query: Vec<String> = tokens.iter().map(|t| format!("\"{}\"*", t.replace('"', "\"\""))).collect();
+                sql_query(
+                    "SELECT s.`id` AS `id`, -bm25(`stats_fts`) AS `score` \
+                     FROM `stats_fts` JOIN `stats` s ON s.`id` = `stats_fts`.`rowid` \
+                     JOIN `groups` g ON g.`id` = s.`group_id` \
+                     WHERE `stats_fts` MATCH ? AND s.`workspace_id` = ? AND s.`status` = ? AND g.`type` IN (?, ?, ?) \
+                     ORDER BY `score` DESC, s.`id` ASC LIMIT ?",
+                )
+                .bind::<Text, _>(query.join(" "))
+                .bind::<Integer, _>(workspace_id)
+                .bind::<Integer, _>(Status::Enabled)
+                .bind::<Integer, _>(GroupType::Local)
+                .bind::<Integer, _>(GroupType::Remote)
+                .bind::<Integer, _>(GroupType::Pushed)
+                .bind::<BigInt, _>(limit)
+                .load(conn)?
+            }
+        };
+        let ids: Vec<i32> = hits.iter().map(|h| h.id).collect();
+        let mut stats: HashMap<i32, Stat> = Stats::select(conn, &ids)?.into_iter().map(|s| (s.id, s)).collect();
+        Ok(hits.into_iter().filter_map(|h| stats.remove(&h.id).map(|s| (s, h.score))).collect())
+    }
+}
+
+#[derive(QueryableByName)]
+struct PathSearchHit {
+    #[diesel(sql_type = Integer)]
+    id: i32,
+    #[diesel(sql_type = Double)]
+    score: f64,
+}
+
+pub fn tokenize_path(s: &str) -> Vec<&str> {
+    s.split(|c: char| c == '/' || c == '_' || c == '.' || c.is_whitespace()).filter(|t| !t.is_empty()).collect()
 }
 
 #[derive(Default, Debug, Clone)]
@@ -170,6 +499,12 @@ pub struct StatSearchCondition<'a> {
     pub paths: Option<Vec<&'a str>>,
     pub path_prefix: Option<&'a str>,
     pub path_partial: Option<&'a str>,
+    /// Case-insensitive glob, where `*` also matches `/` like `**` and `?` matches any one character.
+    /// Non-ASCII letters are folded too, except on Postgres databases with the `C` ctype.
+    pub path_glob: Option<&'a str>,
+    /// Case-insensitive extensions, with or without the leading dot.
+    pub extensions: Option<Vec<&'a str>>,
+    pub digest_prefix: Option<&'a str>,
     pub statuses: Option<Vec<Status>>,
     pub mtime_after: Option<NaiveDateTime>,
     pub mtime_before: Option<NaiveDateTime>,
@@ -178,15 +513,125 @@ pub struct StatSearchCondition<'a> {
     pub updated_at_after: Option<NaiveDateTime>,
     pub updated_at_before: Option<NaiveDateTime>,
     pub order: Option<StatOrder>,
+    pub after: Option<StatCursor>,
     pub limit: Option<i64>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Default, Debug, Clone)]
 pub enum StatOrder {
     PathAsc,
     PathDesc,
     UpdatedAtAsc,
+    #[default]
     UpdatedAtDesc,
+    SizeAsc,
+    SizeDesc,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatCursor {
+    pub id: i32,
+    pub path: String,
+    pub updated_at: NaiveDateTime,
+    #[serde(default)]
+    pub size: Option<i64>,
+}
+
+impl StatCursor {
+    pub fn new(stat: &Stat) -> StatCursor {
+        StatCursor { id: stat.id, path: stat.path.clone(), updated_at: stat.updated_at, size: stat.size }
+    }
+
+    pub fn encode(&self) -> String {
+        encode_cursor(self)
+    }
+
+    pub fn decode(s: &str) -> DomainResult<StatCursor> {
+        decode_cursor(s)
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct HistorySearchCondition<'a> {
+    pub group_ids: Option<Vec<i32>>,
+    pub path_prefix: Option<&'a str>,
+    pub footprint_id: Option<i32>,
+    pub after: Option<HistoryCursor>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryCursor {
+    pub id: i32,
+}
+
+impl HistoryCursor {
+    pub fn new(history: &History) -> HistoryCursor {
+        HistoryCursor { id: history.id }
+    }
+
+    pub fn encode(&self) -> String {
+        encode_cursor(self)
+    }
+
+    pub fn decode(s: &str) -> DomainResult<HistoryCursor> {
+        decode_cursor(s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttrCursor {
+    pub id: i32,
+}
+
+impl AttrCursor {
+    pub fn new(attr: &Attr) -> AttrCursor {
+        AttrCursor { id: attr.id }
+    }
+
+    pub fn encode(&self) -> String {
+        encode_cursor(self)
+    }
+
+    pub fn decode(s: &str) -> DomainResult<AttrCursor> {
+        decode_cursor(s)
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct SnapshotCondition<'a> {
+    pub path_prefix: Option<&'a str>,
+    pub after: Option<SnapshotCursor>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotCursor {
+    pub path: String,
+}
+
+impl SnapshotCursor {
+    pub fn new(history: &History) -> SnapshotCursor {
+        SnapshotCursor { path: history.path.clone() }
+    }
+
+    pub fn encode(&self) -> String {
+        encode_cursor(self)
+    }
+
+    pub fn decode(s: &str) -> DomainResult<SnapshotCursor> {
+        decode_cursor(s)
+    }
+}
+
+fn encode_cursor<T: Serialize>(cursor: &T) -> String {
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(cursor).unwrap())
+}
+
+fn decode_cursor<T: DeserializeOwned>(s: &str) -> DomainResult<T> {
+    let bytes =
+        URL_SAFE_NO_PAD.decode(s).map_err(|_| DomainError::params("cursor", format!("invalid cursor: {}", s)))?;
+    serde_json::from_slice(&bytes).map_err(|_| DomainError::params("cursor", format!("invalid cursor: {}", s)))
 }
 
 pub struct Attrs;
@@ -202,4 +647,27 @@ impl Attrs {
     impl_select!(Connection, attrs, Attr; select_by_key_and_value_summary, workspace_id: i32, key: &str, value_summary: &str);
 
     impl_select!(Connection, attrs, Attr; select_by_target_footprint_id, workspace_id: i32, target_footprint_id: i32);
+
+    pub fn search(
+        conn: &mut Connection,
+        workspace_id: i32,
+        key: &str,
+        value_summary: Option<&str>,
+        after: Option<&AttrCursor>,
+        limit: i64,
+    ) -> DomainResult<Vec<Attr>> {
+        use crate::db::schema::attrs::dsl;
+        let mut q = dsl::attrs
+            .filter(dsl::workspace_id.eq(workspace_id))
+            .filter(dsl::key.eq(key))
+            .filter(dsl::status.eq(Status::Enabled))
+            .into_boxed();
+        if let Some(value_summary) = value_summary {
+            q = q.filter(dsl::value_summary.eq(value_summary));
+        }
+        if let Some(after) = after {
+            q = q.filter(dsl::id.gt(after.id));
+        }
+        Ok(q.order(dsl::id.asc()).limit(limit).load::<Attr>(conn)?)
+    }
 }
@@ -2,13 +2,20 @@ use std::{
     any::Any,
     collections::HashMap,
     fmt::{self, Display},
+    io,
 };
 
 #[derive(Debug)]
 pub enum DomainError {
     Params(Vec<ErrorDetail>),
+    NotFound(ErrorDetail),
+    AlreadyExists(ErrorDetail),
+    Conflict(ErrorDetail),
+    UnsupportedScheme(ErrorDetail),
+    Io(io::Error),
+    Db(diesel::result::Error),
     Internal(ErrorDetail),
-    Others(Box<dyn std::error::Error>),
+    Others(Box<dyn std::error::Error + Send + Sync>),
 }
 
 pub type DomainResult<T> = std::result::Result<T, DomainError>;
@@ -17,32 +24,153 @@ impl Display for DomainError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             DomainError::Params(payload) => {
-                for d in payload.iter() {
-                    f.write_str(d.to_string().as_str())?;
-                    f.write_str(", ")?;
+                for (i, d) in payload.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    d.fmt(f)?;
                 }
                 Ok(())
             }
-            DomainError::Internal(payload) => payload.fmt(f),
+            DomainError::NotFound(payload)
+            | DomainError::AlreadyExists(payload)
+            | DomainError::Conflict(payload)
+            | DomainError::UnsupportedScheme(payload)
+            | DomainError::Internal(payload) => payload.fmt(f),
+            DomainError::Io(e) => e.fmt(f),
+            DomainError::Db(e) => e.fmt(f),
             DomainError::Others(payload) => payload.fmt(f),
         }
     }
 }
 
-impl std::error::Error for DomainError {}
+impl std::error::Error for DomainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DomainError::Io(e) => Some(e),
+            DomainError::Db(e) => Some(e),
+            DomainError::Others(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 impl DomainError {
     pub fn params(code: &'static str, message: String) -> DomainError {
         DomainError::Params(vec![ErrorDetail::new(code, message)])
     }
 
+    pub fn not_found(code: &'static str, message: String) -> DomainError {
+        DomainError::NotFound(ErrorDetail::new(code, message))
+    }
+
+    pub fn already_exists(code: &'static str, message: String) -> DomainError {
+        DomainError::AlreadyExists(ErrorDetail::new(code, message))
+    }
+
+    pub fn conflict(code: &'static str, message: String) -> DomainError {
+        DomainError::Conflict(ErrorDetail::new(code, message))
+    }
+
+    pub fn unsupported_scheme(code: &'static str, message: String) -> DomainError {
+        DomainError::UnsupportedScheme(ErrorDetail::new(code, message))
+    }
+
     pub fn internal(code: &'static str, message: String) -> DomainError {
         DomainError::Internal(ErrorDetail::new(code, message))
     }
+
+    pub fn other<E: Into<Box<dyn std::error::Error + Send + Sync>>>(e: E) -> DomainError {
+        DomainError::Others(e.into())
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            DomainError::Params(payload) => payload.first().map(|d| d.code).unwrap_or("params"),
+            DomainError::NotFound(payload)
+            | DomainError::AlreadyExists(payload)
+            | DomainError::Conflict(payload)
+            | DomainError::UnsupportedScheme(payload)
+            | DomainError::Internal(payload) => payload.code,
+            DomainError::Io(_) => "io",
+            DomainError::Db(_) => "db",
+            DomainError::Others(_) => "others",
+        }
+    }
+
+    pub fn details(&self) -> &[ErrorDetail] {
+        match self {
+            DomainError::Params(payload) => payload,
+            DomainError::NotFound(payload)
+            | DomainError::AlreadyExists(payload)
+            | DomainError::Conflict(payload)
+            | DomainError::UnsupportedScheme(payload)
+            | DomainError::Internal(payload) => std::slice::from_ref(payload),
+            _ => &[],
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            DomainError::Params(_) => 64,
+            DomainError::NotFound(_) => 66,
+            DomainError::Db(_) => 69,
+            DomainError::Internal(_) | DomainError::Others(_) => 70,
+            DomainError::AlreadyExists(_) => 73,
+            DomainError::Io(_) => 74,
+            DomainError::Conflict(_) => 75,
+            DomainError::UnsupportedScheme(_) => 76,
+        }
+    }
+
+    pub fn http_status(&self) -> u16 {
+        match self {
+            DomainError::Params(_) | DomainError::UnsupportedScheme(_) => 400,
+            DomainError::NotFound(_) => 404,
+            DomainError::AlreadyExists(_) | DomainError::Conflict(_) => 409,
+            DomainError::Io(_) | DomainError::Db(_) | DomainError::Internal(_) | DomainError::Others(_) => 500,
+        }
+    }
+}
+
+impl From<io::Error> for DomainError {
+    fn from(e: io::Error) -> Self {
+        DomainError::Io(e)
+    }
+}
+
+impl From<diesel::result::Error> for DomainError {
+    fn from(e: diesel::result::Error) -> Self {
+        DomainError::Db(e)
+    }
 }
 
-impl From<Box<dyn std::error::Error>> for DomainError {
-    fn from(e: Box<dyn std::error::Error>) -> Self {
+impl From<diesel::ConnectionError> for DomainError {
+    fn from(e: diesel::ConnectionError) -> Self {
+        DomainError::Others(Box::new(e))
+    }
+}
+
+impl From<url::ParseError> for DomainError {
+    fn from(e: url::ParseError) -> Self {
+        DomainError::params("url", e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for DomainError {
+    fn from(e: serde_json::Error) -> Self {
+        DomainError::Others(Box::new(e))
+    }
+}
+
+impl From<ureq::Error> for DomainError {
+    fn from(e: ureq::Error) -> Self {
+        DomainError::Others(Box::new(e))
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for DomainError {
+    fn from(e: Box<dyn std::error::Error + Send + Sync>) -> Self {
         DomainError::Others(e)
     }
 }
@@ -51,7 +179,7 @@ impl From<Box<dyn std::error::Error>> for DomainError {
 pub struct ErrorDetail {
     code: &'static str,
     message: String,
-    user_info: Option<HashMap<String, Box<dyn Any>>>,
+    user_info: Option<HashMap<String, Box<dyn Any + Send + Sync>>>,
 }
 
 impl Display for ErrorDetail {
@@ -64,4 +192,16 @@ impl ErrorDetail {
     pub fn new(code: &'static str, message: String) -> ErrorDetail {
         ErrorDetail { code, message, user_info: None }
     }
+
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn user_info(&self) -> Option<&HashMap<String, Box<dyn Any + Send + Sync>>> {
+        self.user_info.as_ref()
+    }
 }
@@ -0,0 +1,99 @@
+use std::io::{BufRead, Write};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{DomainError, DomainResult},
+    Attr, Footprint, Group, History, Workspace,
+};
+
+pub const FORMAT_NAME: &str = "ichno-export";
+pub const FORMAT_VERSION: i32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Header {
+    pub format: String,
+    pub version: i32,
+    #[serde(default)]
+    pub group_name: Option<String>,
+    pub since: Option<i32>,
+    pub exported_at: NaiveDateTime,
+}
+
+impl Header {
+    pub fn new(group_name: &str, since: Option<i32>, exported_at: NaiveDateTime) -> Header {
+        Header {
+            format: FORMAT_NAME.to_owned(),
+            version: FORMAT_VERSION,
+            group_name: Some(group_name.to_owned()),
+            since,
+            exported_at,
+        }
+    }
+
+    pub fn validate(&self) -> DomainResult<()> {
+        if self.format != FORMAT_NAME {
+            return Err(DomainError::params("format", format!("unknown format: {}", &self.format)));
+        }
+        if self.version > FORMAT_VERSION {
+            return Err(DomainError::params("version", format!("unsupported version: {}", self.version)));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContentRecord {
+    pub footprint_id: i32,
+    pub digest: String,
+    pub body: String,
+}
+
+impl ContentRecord {
+    pub fn new(footprint: &Footprint, body: &[u8]) -> ContentRecord {
+        ContentRecord { footprint_id: footprint.id, digest: footprint.digest.clone(), body: STANDARD.encode(body) }
+    }
+
+    pub fn decode_body(&self) -> DomainResult<Vec<u8>> {
+        STANDARD.decode(&self.body).map_err(DomainError::other)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Footer {
+    pub last_history_id: Option<i32>,
+    pub histories: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum Record {
+    Header(Header),
+    Workspace(Workspace),
+    Group(Group),
+    Footprint(Footprint),
+    Content(ContentRecord),
+    Attr(Attr),
+    History(History),
+    Footer(Footer),
+}
+
+pub fn write_record<W: Write>(w: &mut W, record: &Record) -> DomainResult<()> {
+    serde_json::to_writer(&mut *w, record)?;
+    w.write_all(b"\n")?;
+    Ok(())
+}
+
+pub fn read_record<R: BufRead>(r: &mut R, line: &mut String) -> DomainResult<Option<Record>> {
+    loop {
+        line.clear();
+        if r.read_line(line)? == 0 {
+            return Ok(None);
+        }
+        if !line.trim().is_empty() {
+            return Ok(Some(serde_json::from_str(line)?));
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    fs::{self, File},
+    io::BufReader,
+    path::Path,
+};
+
+use lofty::prelude::*;
+use serde_json::{json, Value};
+
+use crate::error::{DomainError, DomainResult};
+
+pub const HEAD_SIZE: usize = 8192;
+const TEXT_SIZE_MAX: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug)]
+pub struct ExtractedAttr {
+    pub key: String,
+    pub value: Value,
+    pub summary: Option<String>,
+}
+
+impl ExtractedAttr {
+    fn new(key: &str, value: Value, summary: Option<String>) -> ExtractedAttr {
+        ExtractedAttr { key: key.to_owned(), value, summary }
+    }
+}
+
+pub trait AttrExtractor: Debug {
+    fn name(&self) -> &str;
+    fn keys(&self) -> &[&str];
+    fn extract(&self, path: &Path, head: &[u8]) -> DomainResult<Vec<ExtractedAttr>>;
+}
+
+fn is_text(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none() && head.len() - e.valid_up_to() < 4,
+    }
+}
+
+fn count_lines(bytes: &[u8]) -> usize {
+    let lines = bytes.iter().filter(|&&b| b == b'\n').count();
+    if bytes.last().is_some_and(|&b| b != b'\n') {
+        lines + 1
+    } else {
+        lines
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MimeExtractor;
+
+impl AttrExtractor for MimeExtractor {
+    fn name(&self) -> &str {
+        "mime"
+    }
+
+    fn keys(&self) -> &[&str] {
+        &["mime"]
+    }
+
+    fn extract(&self, _path: &Path, head: &[u8]) -> DomainResult<Vec<ExtractedAttr>> {
+        let mime = if let Some(t) = infer::get(head) {
+            t.mime_type()
+        } else if is_text(head) {
+            "text/plain"
+        } else {
+            "application/octet-stream"
+        };
+        Ok(vec![ExtractedAttr::new("mime", json!(mime), Some(mime.to_owned()))])
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TextExtractor;
+
+impl AttrExtractor for TextExtractor {
+    fn name(&self) -> &str {
+        "text"
+    }
+
+    fn keys(&self) -> &[&str] {
+        &["lines"]
+    }
+
+    fn extract(&self, path: &Path, head: &[u8]) -> DomainResult<Vec<ExtractedAttr>> {
+        if infer::get(head).is_some() || !is_text(head) || fs::metadata(path)?.len() > TEXT_SIZE_MAX {
+            return Ok(vec![]);
+        }
+        let bytes = fs::read(path)?;
+        if std::str::from_utf8(&bytes).is_err() {
+            return Ok(vec![]);
+        }
+        let lines = count_lines(&bytes);
+        Ok(vec![ExtractedAttr::new("lines", json!(lines), Some(lines.to_string()))])
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ImageExtractor;
+
+impl AttrExtractor for ImageExtractor {
+    fn name(&self) -> &str {
+        "image"
+    }
+
+    fn keys(&self) -> &[&str] {
+        &["image", "exif"]
+    }
+
+    fn extract(&self, path: &Path, head: &[u8]) -> DomainResult<Vec<ExtractedAttr>> {
+        let image_type = if let Ok(image_type) = imagesize::image_type(head) {
+            image_type
+        } else {
+            return Ok(vec![]);
+        };
+        let size = imagesize::size(path).map_err(DomainError::other)?;
+        let mut attrs = vec![ExtractedAttr::new(
+            "image",
+            json!({ "format": format!("{:?}", image_type).to_lowercase(), "width": size.width, "height": size.height }),
+            Some(format!("{}x{}", size.width, size.height)),
+        )];
+        let mut reader = BufReader::new(File::open(path)?);
+        if let Ok(exif) = exif::Reader::new().read_from_container(&mut reader) {
+            let mut fields = BTreeMap::new();
+            for field in exif.fields().filter(|f| f.ifd_num == exif::In::PRIMARY) {
+                fields.insert(field.tag.to_string(), field.display_value().with_unit(&exif).to_string());
+            }
+            let summary = [exif::Tag::Make, exif::Tag::Model]
+                .iter()
+                .filter_map(|&tag| exif.get_field(tag, exif::In::PRIMARY))
+                .map(|f| f.display_value().to_string().trim_matches('"').trim().to_owned())
+                .collect::<Vec<_>>()
+                .join(" ");
+            attrs.push(ExtractedAttr::new("exif", json!(fields), Some(summary).filter(|s| !s.is_empty())));
+        }
+        Ok(attrs)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AudioExtractor;
+
+impl AttrExtractor for AudioExtractor {
+    fn name(&self) -> &str {
+        "audio"
+    }
+
+    fn keys(&self) -> &[&str] {
+        &["audio"]
+    }
+
+    fn extract(&self, path: &Path, head: &[u8]) -> DomainResult<Vec<ExtractedAttr>> {
+        if !infer::get(head).is_some_and(|t| t.matcher_type() == infer::MatcherType::Audio) {
+            return Ok(vec![]);
+        }
+        let tagged_file = lofty::read_from_path(path).map_err(DomainError::other)?;
+        let properties = tagged_file.properties();
+        let mut value = json!({
+            "duration": properties.duration().as_secs_f64(),
+            "bitrate": properties.audio_bitrate(),
+            "sample_rate": properties.sample_rate(),
+            "channels": properties.channels(),
+        });
+        let mut summary = None;
+        if let Some(tag) = tagged_file.primary_tag().or_else(|| tagged_file.first_tag()) {
+            value["title"] = json!(tag.title());
+            value["artist"] = json!(tag.artist());
+            value["album"] = json!(tag.album());
+            value["genre"] = json!(tag.genre());
+            value["year"] = json!(tag.year());
+            value["track"] = json!(tag.track());
+            summary = tag.artist().map(|s| s.into_owned());
+        }
+        Ok(vec![ExtractedAttr::new("audio", value, summary)])
+    }
+}
+
+pub fn default_extractors() -> Vec<Box<dyn AttrExtractor>> {
+    vec![
+        Box::<MimeExtractor>::default(),
+        Box::<TextExtractor>::default(),
+        Box::<ImageExtractor>::default(),
+        Box::<AudioExtractor>::default(),
+    ]
+}
+
+pub fn find_extractors(names: &[String]) -> DomainResult<Vec<Box<dyn AttrExtractor>>> {
+    if names.is_empty() {
+        return Ok(default_extractors());
+    }
+    let mut extractors = default_extractors();
+    for name in names.iter() {
+        if !extractors.iter().any(|e| e.name() == name) {
+            return Err(DomainError::params("extractor", format!("unknown extractor: {}", name)));
+        }
+    }
+    extractors.retain(|e| names.iter().any(|name| e.name() == name));
+    Ok(extractors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_text() {
+        assert!(is_text(b""));
+        assert!(is_text(b"hello\nworld\n"));
+        assert!(is_text("日本語のテキスト".as_bytes()));
+        // A multibyte character cut off at the end of the head is still text.
+        let bytes = "テキスト".as_bytes();
+        assert!(is_text(&bytes[..bytes.len() - 1]));
+        assert!(!is_text(b"hello\0world"));
+        assert!(!is_text(b"abc\xff\xfedef"));
+        assert!(!is_text(b"abc\xe3\x81def"));
+    }
+
+    #[test]
+    fn test_count_lines() {
+        assert_eq!(count_lines(b""), 0);
+        assert_eq!(count_lines(b"a"), 1);
+        assert_eq!(count_lines(b"a\n"), 1);
+        assert_eq!(count_lines(b"a\nb"), 2);
+        assert_eq!(count_lines(b"a\n\nb\n"), 3);
+        assert_eq!(count_lines(b"\n"), 1);
+    }
+
+    #[test]
+    fn test_find_extractors() {
+        let names = |extractors: Vec<Box<dyn AttrExtractor>>| -> Vec<String> {
+            extractors.iter().map(|e| e.name().to_owned()).collect()
+        };
+        assert_eq!(names(find_extractors(&[]).unwrap()), vec!["mime", "text", "image", "audio"]);
+        let found = find_extractors(&["text".to_owned(), "mime".to_owned()]).unwrap();
+        assert_eq!(names(found), vec!["mime", "text"]);
+        let err = find_extractors(&["mime".to_owned(), "video".to_owned()]).unwrap_err();
+        assert_eq!(err.code(), "extractor");
+    }
+}
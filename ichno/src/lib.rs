@@ -7,18 +7,24 @@ extern crate log;
 extern crate optional_derive;
 
 pub mod actions;
+pub mod codec;
 pub mod db;
 pub mod error;
+pub mod export;
+pub mod extract;
+pub mod push;
+pub mod store;
 
 pub(crate) mod models;
 
 mod constants;
 
 pub use constants::{
-    ContentType, GroupType, Status, ATTR_GROUP_NAME, DEFAULT_GROUP_NAME, DEFAULT_WORKSPACE_NAME, META_GROUP_NAME,
+    ContentCodec, ContentType, GroupType, Status, ATTR_GROUP_NAME, DEFAULT_GROUP_NAME, DEFAULT_WORKSPACE_NAME,
+    META_GROUP_NAME,
 };
 pub use models::{
-    Attr, AttrInsertForm, AttrUpdateForm, Content, ContentInsertForm, Footprint, FootprintInsertForm, Group,
-    GroupInsertForm, GroupUpdateForm, History, HistoryInsertForm, Stat, StatInsertForm, StatUpdateForm, Workspace,
-    WorkspaceInsertForm, WorkspaceUpdateForm,
+    Attr, AttrInsertForm, AttrUpdateForm, Content, ContentInsertForm, ContentUpdateForm, Footprint,
+    FootprintInsertForm, Group, GroupInsertForm, GroupUpdateForm, History, HistoryInsertForm, Stat, StatInsertForm,
+    StatUpdateForm, Workspace, WorkspaceInsertForm, WorkspaceUpdateForm,
 };
@@ -1,9 +1,12 @@
 use chrono::NaiveDateTime;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::db::schema::{attrs, contents, footprints, groups, histories, stats, workspaces};
+use crate::{
+    db::schema::{attrs, contents, footprints, groups, histories, stats, workspaces},
+    ContentCodec, ContentType, GroupType, Status,
+};
 
-#[derive(Clone, Debug, PartialEq, Serialize, Identifiable, Queryable)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Identifiable, Queryable)]
 #[diesel(table_name = footprints)]
 pub struct Footprint {
     pub id: i32,
@@ -15,7 +18,7 @@ pub struct Footprint {
 }
 
 #[derive(Clone, Debug, Insertable)]
-#[diesel(table_name = footprints)]
+#[diesel(table_name = footprints, treat_none_as_default_value = false)]
 pub struct FootprintInsertForm<'a> {
     pub digest: &'a str,
     pub size: i64,
@@ -30,42 +33,51 @@ pub struct Content {
 
     pub footprint_id: i32,
     pub body: Vec<u8>,
+    pub codec: ContentCodec,
+    pub dictionary_id: Option<i32>,
+    pub location: Option<String>,
     pub created_at: NaiveDateTime,
 }
 
-#[derive(Clone, Debug, Insertable)]
-#[diesel(table_name = contents)]
+#[derive(Clone, Debug, Insertable, Optional)]
+#[optional(name = "ContentUpdateForm", derive = "Default, Debug, AsChangeset")]
+#[diesel(table_name = contents, treat_none_as_default_value = false)]
 pub struct ContentInsertForm<'a> {
+    #[optional(skip = true)]
     pub footprint_id: i32,
     pub body: &'a [u8],
+    pub codec: ContentCodec,
+    pub dictionary_id: Option<i32>,
+    pub location: Option<&'a str>,
+    #[optional(skip = true)]
     pub created_at: NaiveDateTime,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Identifiable, Queryable)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Identifiable, Queryable)]
 #[diesel(table_name = workspaces)]
 pub struct Workspace {
     pub id: i32,
 
     pub name: String,
     pub description: String,
-    pub status: i32,
+    pub status: Status,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
 
 #[derive(Clone, Debug, Insertable, Optional)]
 #[optional(name = "WorkspaceUpdateForm", derive = "Default, Debug, AsChangeset")]
-#[diesel(table_name = workspaces)]
+#[diesel(table_name = workspaces, treat_none_as_default_value = false)]
 pub struct WorkspaceInsertForm<'a> {
     pub name: &'a str,
     pub description: &'a str,
-    pub status: i32,
+    pub status: Status,
     pub created_at: NaiveDateTime,
     #[optional(skip = true)]
     pub updated_at: NaiveDateTime,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Identifiable, Queryable)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Identifiable, Queryable)]
 #[diesel(table_name = groups)]
 pub struct Group {
     pub id: i32,
@@ -75,11 +87,15 @@ pub struct Group {
     pub name: String,
     pub url: String,
     #[serde(rename = "type")]
-    pub type_: i32,
+    pub type_: GroupType,
     pub description: String,
-    pub status: i32,
+    pub status: Status,
 
     pub group_stat_id: Option<i32>,
+    pub pulled_history_id: Option<i32>,
+    pub pulled_etag: Option<String>,
+    pub pulled_last_modified: Option<String>,
+    pub pulled_source_id: Option<String>,
 
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
@@ -87,25 +103,29 @@ pub struct Group {
 
 #[derive(Clone, Debug, Insertable, Optional)]
 #[optional(name = "GroupUpdateForm", derive = "Default, Debug, AsChangeset")]
-#[diesel(table_name = groups)]
+#[diesel(table_name = groups, treat_none_as_default_value = false)]
 pub struct GroupInsertForm<'a> {
     #[optional(skip = true)]
     pub workspace_id: i32,
 
     pub name: &'a str,
     pub url: &'a str,
-    pub type_: i32,
+    pub type_: GroupType,
     pub description: &'a str,
-    pub status: i32,
+    pub status: Status,
 
     pub group_stat_id: Option<i32>,
+    pub pulled_history_id: Option<i32>,
+    pub pulled_etag: Option<&'a str>,
+    pub pulled_last_modified: Option<&'a str>,
+    pub pulled_source_id: Option<&'a str>,
 
     #[optional(skip = true)]
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Identifiable, Queryable)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Identifiable, Queryable)]
 #[diesel(table_name = histories)]
 pub struct History {
     pub id: i32,
@@ -115,7 +135,7 @@ pub struct History {
     pub path: String,
     pub version: i32,
 
-    pub status: i32,
+    pub status: Status,
     pub mtime: Option<NaiveDateTime>,
     pub footprint_id: Option<i32>,
     pub digest: Option<String>,
@@ -125,14 +145,14 @@ pub struct History {
 }
 
 #[derive(Clone, Debug, Insertable)]
-#[diesel(table_name = histories)]
+#[diesel(table_name = histories, treat_none_as_default_value = false)]
 pub struct HistoryInsertForm<'a> {
     pub workspace_id: i32,
     pub group_id: i32,
     pub path: &'a str,
     pub version: i32,
 
-    pub status: i32,
+    pub status: Status,
     pub mtime: Option<NaiveDateTime>,
     pub footprint_id: Option<i32>,
     pub digest: Option<&'a str>,
@@ -153,7 +173,7 @@ pub struct Stat {
     pub history_id: i32,
 
     pub version: i32,
-    pub status: i32,
+    pub status: Status,
     pub mtime: Option<NaiveDateTime>,
     pub footprint_id: Option<i32>,
 
@@ -167,7 +187,7 @@ pub struct Stat {
 
 #[derive(Clone, Debug, Insertable, Optional)]
 #[optional(name = "StatUpdateForm", derive = "Default, Debug, AsChangeset")]
-#[diesel(table_name = stats)]
+#[diesel(table_name = stats, treat_none_as_default_value = false)]
 pub struct StatInsertForm<'a> {
     #[optional(skip = true)]
     pub workspace_id: i32,
@@ -179,7 +199,7 @@ pub struct StatInsertForm<'a> {
     pub history_id: i32,
 
     pub version: i32,
-    pub status: i32,
+    pub status: Status,
     pub mtime: Option<NaiveDateTime>,
     pub footprint_id: Option<i32>,
 
@@ -192,7 +212,7 @@ pub struct StatInsertForm<'a> {
     pub updated_at: NaiveDateTime,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Identifiable, Queryable)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Identifiable, Queryable)]
 #[diesel(table_name = attrs)]
 pub struct Attr {
     pub id: i32,
@@ -203,9 +223,9 @@ pub struct Attr {
     pub key: String,
     pub value_footprint_id: i32,
     pub value_digest: String,
-    pub value_content_type: i32,
+    pub value_content_type: ContentType,
     pub value_summary: Option<String>,
-    pub status: i32,
+    pub status: Status,
     pub attr_stat_id: Option<i32>,
 
     pub created_at: NaiveDateTime,
@@ -214,7 +234,7 @@ pub struct Attr {
 
 #[derive(Clone, Debug, Insertable, Optional)]
 #[optional(name = "AttrUpdateForm", derive = "Default, Debug, AsChangeset")]
-#[diesel(table_name = attrs)]
+#[diesel(table_name = attrs, treat_none_as_default_value = false)]
 pub struct AttrInsertForm<'a> {
     #[optional(skip = true)]
     pub workspace_id: i32,
@@ -226,9 +246,9 @@ pub struct AttrInsertForm<'a> {
     pub key: &'a str,
     pub value_footprint_id: i32,
     pub value_digest: &'a str,
-    pub value_content_type: i32,
+    pub value_content_type: ContentType,
     pub value_summary: Option<&'a str>,
-    pub status: i32,
+    pub status: Status,
     pub attr_stat_id: Option<i32>,
 
     #[optional(skip = true)]
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use ureq::{Agent, AgentBuilder, Request};
+use url::Url;
+
+use crate::{
+    error::{DomainError, DomainResult},
+    Footprint, History,
+};
+
+const TIMEOUT_SECS: u64 = 60;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Changeset {
+    pub url: String,
+    pub source_id: String,
+    pub from_history_id: Option<i32>,
+    pub histories: Vec<History>,
+    pub footprints: Vec<Footprint>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PushCursor {
+    pub pulled_history_id: Option<i32>,
+    pub pulled_source_id: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PushResult {
+    pub pulled_history_id: Option<i32>,
+    pub histories: usize,
+    pub stats: usize,
+    pub footprints: usize,
+}
+
+#[derive(Debug)]
+pub struct PushClient {
+    agent: Agent,
+    endpoint: Url,
+    token: Option<String>,
+}
+
+impl PushClient {
+    pub fn new(
+        server_url: &Url,
+        workspace_name: &str,
+        group_name: &str,
+        token: Option<String>,
+    ) -> DomainResult<PushClient> {
+        let mut server_url = server_url.clone();
+        if !server_url.path().ends_with('/') {
+            server_url.set_path(&format!("{}/", server_url.path()));
+        }
+        let mut endpoint = server_url.clone();
+        endpoint
+            .path_segments_mut()
+            .map_err(|_| DomainError::params("url", format!("invalid server url: {}", server_url)))?
+            .pop_if_empty()
+            .extend(&[workspace_name, "groups", group_name, "push"]);
+        let agent = AgentBuilder::new().timeout(Duration::from_secs(TIMEOUT_SECS)).build();
+        Ok(PushClient { agent, endpoint, token })
+    }
+
+    fn request(&self, method: &str) -> Request {
+        let req = self.agent.request_url(method, &self.endpoint);
+        match self.token.as_ref() {
+            Some(token) => req.set("Authorization", &format!("Bearer {}", token)),
+            None => req,
+        }
+    }
+
+    fn call(&self, req: Request, body: Option<String>) -> DomainResult<String> {
+        let resp = match body {
+            Some(body) => req.set("Content-Type", "application/json").send_string(&body),
+            None => req.call(),
+        };
+        match resp {
+            Ok(resp) => Ok(resp.into_string()?),
+            Err(ureq::Error::Status(status, resp)) => {
+                let body = resp.into_string().unwrap_or_default();
+                let message = format!("{}: {}: {}", &self.endpoint, status, body);
+                match status {
+                    409 => Err(DomainError::conflict("push", message)),
+                    _ => Err(DomainError::internal("push", message)),
+                }
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn fetch_cursor(&self) -> DomainResult<PushCursor> {
+        let body = self.call(self.request("GET"), None)?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    pub fn send(&self, changeset: &Changeset) -> DomainResult<PushResult> {
+        let body = self.call(self.request("POST"), Some(serde_json::to_string(changeset)?))?;
+        Ok(serde_json::from_str(&body)?)
+    }
+}
@@ -0,0 +1,114 @@
+use std::{
+    fmt::Debug,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use url::Url;
+
+use crate::error::{DomainError, DomainResult};
+
+const ZSTD_EXTENSION: &str = "zst";
+const ZSTD_LEVEL: i32 = 3;
+
+pub trait ContentStore: Debug {
+    fn put(&self, digest: &str, body: &[u8]) -> DomainResult<String>;
+}
+
+#[derive(Debug)]
+pub struct LocalContentStore {
+    pub root: PathBuf,
+    pub compression: bool,
+}
+
+impl LocalContentStore {
+    pub fn new<P: AsRef<Path>>(root: P, compression: bool) -> DomainResult<LocalContentStore> {
+        fs::create_dir_all(root.as_ref())?;
+        let root = root.as_ref().canonicalize()?;
+        Ok(LocalContentStore { root, compression })
+    }
+
+    fn path_of(&self, digest: &str) -> DomainResult<PathBuf> {
+        if digest.len() <= 2 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(DomainError::params("digest", format!("invalid digest: {}", digest)));
+        }
+        let (shard, rest) = digest.split_at(2);
+        let path = self.root.join(shard).join(rest);
+        Ok(if self.compression { path.with_extension(ZSTD_EXTENSION) } else { path })
+    }
+}
+
+impl ContentStore for LocalContentStore {
+    fn put(&self, digest: &str, body: &[u8]) -> DomainResult<String> {
+        let path = self.path_of(digest)?;
+        if !path.exists() {
+            let parent = path.parent().unwrap();
+            fs::create_dir_all(parent)?;
+            let temp_path = parent.join(format!(".{}.tmp", digest));
+            {
+                let mut f = File::create(&temp_path)?;
+                if self.compression {
+                    zstd::stream::copy_encode(body, &mut f, ZSTD_LEVEL)?;
+                } else {
+                    f.write_all(body)?;
+                }
+                f.sync_all()?;
+            }
+            fs::rename(&temp_path, &path)?;
+            debug!("content stored: {:?}", &path);
+        }
+        Ok(Url::from_file_path(&path).unwrap().to_string())
+    }
+}
+
+pub fn load(location: &str) -> DomainResult<Vec<u8>> {
+    let url = Url::parse(location)?;
+    match url.scheme() {
+        "file" => {
+            let path = url
+                .to_file_path()
+                .map_err(|_| DomainError::params("location", format!("invalid location: {}", location)))?;
+            let bytes = fs::read(&path)?;
+            if path.extension().is_some_and(|ext| ext == ZSTD_EXTENSION) {
+                Ok(zstd::stream::decode_all(bytes.as_slice())?)
+            } else {
+                Ok(bytes)
+            }
+        }
+        scheme => Err(DomainError::params("location", format!("unsupported scheme: {}", scheme))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_of() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalContentStore::new(dir.path(), false).unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        assert_eq!(root.join("ab").join("cdef"), store.path_of("abcdef").unwrap());
+        assert!(store.path_of("ab").is_err());
+        assert!(store.path_of("../etc").is_err());
+
+        let store = LocalContentStore::new(dir.path(), true).unwrap();
+        assert_eq!(root.join("ab").join("cdef.zst"), store.path_of("abcdef").unwrap());
+    }
+
+    #[test]
+    fn test_put_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let body = "hello, world\n".repeat(100);
+        for compression in [false, true] {
+            let store = LocalContentStore::new(dir.path().join(compression.to_string()), compression).unwrap();
+            let location = store.put("0123456789", body.as_bytes()).unwrap();
+            assert_eq!(location, store.put("0123456789", b"ignored").unwrap());
+            let stored = fs::read(store.path_of("0123456789").unwrap()).unwrap();
+            assert_eq!(!compression, stored == body.as_bytes());
+            assert_eq!(body.as_bytes(), load(&location).unwrap().as_slice());
+        }
+        assert!(load("s3://bucket/key").is_err());
+    }
+}
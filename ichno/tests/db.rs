@@ -0,0 +1,515 @@
+use std::{cell::Cell, fs, path::Path, rc::Rc};
+
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use diesel::connection::SimpleConnection;
+use ichno::{
+    actions::{self, ContentPolicy, Context, ExtractPolicy, RestoreOptions},
+    db::{
+        self, Connection, Contents, Footprints, Groups, Histories, HistoryCursor, HistorySearchCondition,
+        SnapshotCondition, SnapshotCursor, StatCursor, StatOrder, StatSearchCondition, Stats, Workspaces,
+    },
+    error::DomainError,
+    extract::find_extractors,
+    ContentCodec, ContentType, FootprintInsertForm, GroupInsertForm, GroupType, HistoryInsertForm, StatInsertForm,
+    Status, WorkspaceInsertForm,
+};
+
+fn setup() -> (Connection, NaiveDateTime) {
+    let mut conn = db::establish(":memory:").unwrap();
+    db::migrate(&mut conn).unwrap();
+    let now = NaiveDateTime::parse_from_str("2020-01-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    (conn, now)
+}
+
+#[test]
+fn test_insert_all_and_select_by_digests() {
+    let (mut conn, now) = setup();
+    let digests = ["00".repeat(32), "11".repeat(32), "22".repeat(32)];
+    let forms: Vec<_> =
+        digests.iter().map(|digest| FootprintInsertForm { digest, size: 1, fast_digest: 0, created_at: now }).collect();
+    assert_eq!(3, Footprints::insert_all(&mut conn, &forms).unwrap());
+    assert_eq!(0, Footprints::insert_all(&mut conn, &[]).unwrap());
+
+    let found = Footprints::select_by_digests(&mut conn, &[&digests[0], &digests[2]]).unwrap();
+    let mut found: Vec<_> = found.into_iter().map(|f| f.digest).collect();
+    found.sort();
+    assert_eq!(vec![digests[0].clone(), digests[2].clone()], found);
+    assert!(Footprints::find_by_digest(&mut conn, &"33".repeat(32)).unwrap().is_none());
+}
+
+fn insert_stats(conn: &mut Connection, now: NaiveDateTime, paths: &[&str]) -> i32 {
+    let workspace = Workspaces::insert_and_find(
+        conn,
+        &WorkspaceInsertForm {
+            name: "default",
+            description: "",
+            status: Status::Enabled,
+            created_at: now,
+            updated_at: now,
+        },
+    )
+    .unwrap();
+    let group = Groups::insert_and_find(
+        conn,
+        &GroupInsertForm {
+            workspace_id: workspace.id,
+            name: "default",
+            url: "file:///tmp/",
+            type_: GroupType::Remote,
+            description: "",
+            status: Status::Enabled,
+            group_stat_id: None,
+            pulled_history_id: None,
+            pulled_etag: None,
+            pulled_last_modified: None,
+            pulled_source_id: None,
+            created_at: now,
+            updated_at: now,
+        },
+    )
+    .unwrap();
+    for &path in paths {
+        let history = Histories::insert_and_find(
+            conn,
+            &HistoryInsertForm {
+                workspace_id: workspace.id,
+                group_id: group.id,
+                path,
+                version: 1,
+                status: Status::Enabled,
+                mtime: Some(now),
+                footprint_id: None,
+                digest: None,
+                created_at: now,
+                updated_at: now,
+            },
+        )
+        .unwrap();
+        Stats::insert(
+            conn,
+            &StatInsertForm {
+                workspace_id: workspace.id,
+                group_id: group.id,
+                path,
+                history_id: history.id,
+                version: 1,
+                status: Status::Enabled,
+                mtime: Some(now),
+                footprint_id: None,
+                digest: None,
+                size: Some(1),
+                fast_digest: None,
+                created_at: now,
+                updated_at: now,
+            },
+        )
+        .unwrap();
+    }
+    workspace.id
+}
+
+#[test]
+fn test_stats_search() {
+    let (mut conn, now) = setup();
+    let workspace_id = insert_stats(&mut conn, now, &["a/x.txt", "a/y.txt", "b/z.txt"]);
+
+    let cond = StatSearchCondition { path_prefix: Some("a/"), ..Default::default() };
+    assert_eq!(2, Stats::count(&mut conn, workspace_id, &cond).unwrap());
+    let stats = Stats::search(&mut conn, workspace_id, &cond).unwrap();
+    assert!(stats.iter().all(|s| s.path.starts_with("a/")));
+}
+
+#[test]
+fn test_stats_and_histories_pagination() {
+    let (mut conn, now) = setup();
+    let paths = ["a", "b", "c", "d", "e"];
+    let workspace_id = insert_stats(&mut conn, now, &paths);
+
+    // Without an explicit order, pages follow the default order of the most recently updated first.
+    let orders = [
+        None,
+        Some(StatOrder::PathAsc),
+        Some(StatOrder::PathDesc),
+        Some(StatOrder::UpdatedAtAsc),
+        Some(StatOrder::UpdatedAtDesc),
+    ];
+    for order in orders {
+        let mut seen = vec![];
+        let mut after = None;
+        loop {
+            let cond = StatSearchCondition { order: order.clone(), after, limit: Some(2), ..Default::default() };
+            let stats = Stats::search(&mut conn, workspace_id, &cond).unwrap();
+            if stats.is_empty() {
+                break;
+            }
+            let cursor = StatCursor::decode(&StatCursor::new(stats.last().unwrap()).encode()).unwrap();
+            seen.extend(stats.into_iter().map(|s| s.path));
+            after = Some(cursor);
+        }
+        let mut expected: Vec<_> = paths.iter().map(|p| p.to_string()).collect();
+        if matches!(order, None | Some(StatOrder::PathDesc | StatOrder::UpdatedAtDesc)) {
+            expected.reverse();
+        }
+        assert_eq!(expected, seen, "{:?}", order);
+    }
+
+    let cond = HistorySearchCondition { limit: Some(3), ..Default::default() };
+    let first = Histories::search(&mut conn, workspace_id, &cond).unwrap();
+    assert_eq!(vec!["e", "d", "c"], first.iter().map(|h| h.path.as_str()).collect::<Vec<_>>());
+    let cond = HistorySearchCondition { after: Some(HistoryCursor::new(first.last().unwrap())), ..cond };
+    let second = Histories::search(&mut conn, workspace_id, &cond).unwrap();
+    assert_eq!(vec!["b", "a"], second.iter().map(|h| h.path.as_str()).collect::<Vec<_>>());
+    assert!(StatCursor::decode("!").is_err());
+}
+
+#[test]
+fn test_histories_search_by_path_prefix() {
+    let (mut conn, now) = setup();
+    let workspace_id = insert_stats(&mut conn, now, &["a_b/x", "a_b/y", "axb/z", "100%/w"]);
+
+    let search = |conn: &mut Connection, path_prefix: &str| {
+        let cond = HistorySearchCondition { path_prefix: Some(path_prefix), ..Default::default() };
+        let mut paths: Vec<_> =
+            Histories::search(conn, workspace_id, &cond).unwrap().into_iter().map(|h| h.path).collect();
+        paths.sort();
+        paths
+    };
+    assert_eq!(vec!["a_b/x", "a_b/y"], search(&mut conn, "a_b/"));
+    assert_eq!(vec!["100%/w"], search(&mut conn, "100%"));
+    assert!(search(&mut conn, "1%").is_empty());
+}
+
+#[test]
+fn test_stats_search_by_glob_extension_digest_and_size() {
+    let (mut conn, now) = setup();
+    let workspace_id = insert_stats(&mut conn, now, &["a/x.txt", "a/b/y.md", "a/z.rs", "b/w.txt", "c/100%_.md"]);
+    conn.batch_execute(
+        "UPDATE stats SET size = NULL WHERE path = 'a/z.rs'; \
+         UPDATE stats SET size = 30, digest = 'abc0' WHERE path = 'a/x.txt'; \
+         UPDATE stats SET size = 20, digest = 'abd0' WHERE path = 'b/w.txt'",
+    )
+    .unwrap();
+    let search = |conn: &mut Connection, cond: &StatSearchCondition| {
+        let mut paths: Vec<_> = Stats::search(conn, workspace_id, cond).unwrap().into_iter().map(|s| s.path).collect();
+        if cond.order.is_none() {
+            paths.sort();
+        }
+        paths
+    };
+
+    let cond = StatSearchCondition { path_glob: Some("a/*.txt"), ..Default::default() };
+    assert_eq!(vec!["a/x.txt"], search(&mut conn, &cond));
+    let cond =
+        StatSearchCondition { path_glob: Some("?/*"), extensions: Some(vec!["txt", ".rs"]), ..Default::default() };
+    assert_eq!(vec!["a/x.txt", "a/z.rs", "b/w.txt"], search(&mut conn, &cond));
+    let cond = StatSearchCondition { path_glob: Some("*%_*"), ..Default::default() };
+    assert_eq!(vec!["c/100%_.md"], search(&mut conn, &cond));
+    let cond = StatSearchCondition { path_prefix: Some("c/100%"), ..Default::default() };
+    assert_eq!(vec!["c/100%_.md"], search(&mut conn, &cond));
+    let cond = StatSearchCondition { path_prefix: Some("a_"), ..Default::default() };
+    assert!(search(&mut conn, &cond).is_empty());
+    let cond = StatSearchCondition { path_partial: Some("%_"), ..Default::default() };
+    assert_eq!(vec!["c/100%_.md"], search(&mut conn, &cond));
+    let cond = StatSearchCondition { path_partial: Some("x_"), ..Default::default() };
+    assert!(search(&mut conn, &cond).is_empty());
+    let cond = StatSearchCondition { digest_prefix: Some("ab"), ..Default::default() };
+    assert_eq!(vec!["a/x.txt", "b/w.txt"], search(&mut conn, &cond));
+    let cond = StatSearchCondition { digest_prefix: Some("abc"), ..Default::default() };
+    assert_eq!(vec!["a/x.txt"], search(&mut conn, &cond));
+
+    let cond = StatSearchCondition { order: Some(StatOrder::SizeDesc), limit: Some(2), ..Default::default() };
+    let page = Stats::search(&mut conn, workspace_id, &cond).unwrap();
+    assert_eq!(vec!["a/x.txt", "b/w.txt"], page.iter().map(|s| s.path.as_str()).collect::<Vec<_>>());
+    let cond = StatSearchCondition { after: Some(StatCursor::new(page.last().unwrap())), limit: None, ..cond };
+    assert_eq!(vec!["c/100%_.md", "a/b/y.md", "a/z.rs"], search(&mut conn, &cond));
+}
+
+#[test]
+fn test_stats_search_by_glob_and_extension_across_directories_and_cases() {
+    let (mut conn, now) = setup();
+    let workspace_id = insert_stats(&mut conn, now, &["x.md", "a/b/y.md", "a/Z.MD", "d/Été.TXT"]);
+    let search = |conn: &mut Connection, cond: &StatSearchCondition| {
+        let mut paths: Vec<_> = Stats::search(conn, workspace_id, cond).unwrap().into_iter().map(|s| s.path).collect();
+        paths.sort();
+        paths
+    };
+
+    // `*` crosses directories.
+    let cond = StatSearchCondition { path_glob: Some("*.md"), ..Default::default() };
+    assert_eq!(vec!["a/Z.MD", "a/b/y.md", "x.md"], search(&mut conn, &cond));
+    let cond = StatSearchCondition { path_glob: Some("a/*.md"), ..Default::default() };
+    assert_eq!(vec!["a/Z.MD", "a/b/y.md"], search(&mut conn, &cond));
+    let cond = StatSearchCondition { path_glob: Some("?.md"), ..Default::default() };
+    assert_eq!(vec!["x.md"], search(&mut conn, &cond));
+
+    let cond = StatSearchCondition { path_glob: Some("D/éTÉ.*"), ..Default::default() };
+    assert_eq!(vec!["d/Été.TXT"], search(&mut conn, &cond));
+    let cond = StatSearchCondition { extensions: Some(vec!["MD"]), ..Default::default() };
+    assert_eq!(vec!["a/Z.MD", "a/b/y.md", "x.md"], search(&mut conn, &cond));
+    let cond = StatSearchCondition { extensions: Some(vec![".txt"]), ..Default::default() };
+    assert_eq!(vec!["d/Été.TXT"], search(&mut conn, &cond));
+}
+
+#[test]
+fn test_histories_snapshot() {
+    let (mut conn, now) = setup();
+    let workspace_id = insert_stats(&mut conn, now, &["a", "b", "c"]);
+    let group = Groups::find_by_name(&mut conn, workspace_id, "default").unwrap().unwrap();
+    let later = |days| now + chrono::Duration::days(days);
+    for (path, version, status, digest, days) in
+        [("a", 2, Status::Enabled, "a2", 10), ("b", 2, Status::Disabled, "", 10), ("d", 1, Status::Enabled, "d1", 20)]
+    {
+        Histories::insert_and_find(
+            &mut conn,
+            &HistoryInsertForm {
+                workspace_id,
+                group_id: group.id,
+                path,
+                version,
+                status,
+                mtime: Some(later(days)),
+                footprint_id: None,
+                digest: Some(digest).filter(|d| !d.is_empty()),
+                created_at: later(days),
+                updated_at: later(days),
+            },
+        )
+        .unwrap();
+    }
+    let snapshot = |conn: &mut Connection, as_of, cond: &SnapshotCondition| {
+        let histories = Histories::snapshot(conn, group.id, as_of, cond).unwrap();
+        histories.into_iter().map(|h| format!("{}@{}", h.path, h.version)).collect::<Vec<_>>()
+    };
+
+    let cond = SnapshotCondition::default();
+    assert!(snapshot(&mut conn, later(-1), &cond).is_empty());
+    assert_eq!(vec!["a@1", "b@1", "c@1"], snapshot(&mut conn, now, &cond));
+    assert_eq!(vec!["a@2", "c@1"], snapshot(&mut conn, later(15), &cond));
+    assert_eq!(vec!["a@2", "c@1", "d@1"], snapshot(&mut conn, later(30), &cond));
+
+    let cond = SnapshotCondition { limit: Some(2), ..Default::default() };
+    let first = Histories::snapshot(&mut conn, group.id, later(30), &cond).unwrap();
+    let after = SnapshotCursor::decode(&SnapshotCursor::new(first.last().unwrap()).encode()).unwrap();
+    let cond = SnapshotCondition { after: Some(after), ..cond };
+    assert_eq!(vec!["d@1"], snapshot(&mut conn, later(30), &cond));
+    let cond = SnapshotCondition { path_prefix: Some("c"), ..Default::default() };
+    assert_eq!(vec!["c@1"], snapshot(&mut conn, later(30), &cond));
+}
+
+#[test]
+fn test_histories_find_latest_by_path_at() {
+    let (mut conn, now) = setup();
+    let workspace_id = insert_stats(&mut conn, now, &["a"]);
+    let group = Groups::find_by_name(&mut conn, workspace_id, "default").unwrap().unwrap();
+    let later = |days| now + chrono::Duration::days(days);
+    for (version, status, days) in [(2, Status::Enabled, 10), (3, Status::Disabled, 20)] {
+        Histories::insert_and_find(
+            &mut conn,
+            &HistoryInsertForm {
+                workspace_id,
+                group_id: group.id,
+                path: "a",
+                version,
+                status,
+                mtime: Some(later(days)),
+                footprint_id: None,
+                digest: None,
+                created_at: later(days),
+                updated_at: later(days),
+            },
+        )
+        .unwrap();
+    }
+    let version_at = |conn: &mut Connection, path, at| {
+        Histories::find_latest_by_path_at(conn, group.id, path, at).unwrap().map(|h| (h.version, h.status))
+    };
+
+    assert_eq!(None, version_at(&mut conn, "a", later(-1)));
+    assert_eq!(Some((1, Status::Enabled)), version_at(&mut conn, "a", now));
+    assert_eq!(Some((2, Status::Enabled)), version_at(&mut conn, "a", later(10)));
+    assert_eq!(Some((2, Status::Enabled)), version_at(&mut conn, "a", later(15)));
+    assert_eq!(Some((3, Status::Disabled)), version_at(&mut conn, "a", later(30)));
+    assert_eq!(None, version_at(&mut conn, "b", later(30)));
+}
+
+#[test]
+fn test_unknown_enum_values_are_rejected() {
+    let (mut conn, _) = setup();
+    conn.batch_execute(
+        "INSERT INTO workspaces (name, description, status, created_at, updated_at) \
+         VALUES ('broken', '', 7, '2020-01-01 00:00:00', '2020-01-01 00:00:00')",
+    )
+    .unwrap();
+    assert!(Workspaces::find_by_name(&mut conn, "broken").is_err());
+
+    assert_eq!("1", serde_json::to_string(&Status::Enabled).unwrap());
+    assert_eq!(Status::Disabled, serde_json::from_str::<Status>("0").unwrap());
+    assert!(serde_json::from_str::<Status>("7").is_err());
+}
+
+#[test]
+fn test_unsupported_scheme_error() {
+    let e = db::establish("redis://localhost/0").err().unwrap();
+    assert!(matches!(e, DomainError::UnsupportedScheme(_)));
+    assert_eq!("database_url", e.code());
+    assert_eq!(76, e.exit_code());
+    assert_eq!(400, e.http_status());
+}
+
+fn setup_local(dir: &Path) -> (Connection, Rc<Cell<NaiveDateTime>>) {
+    let db_path = dir.join("ichno.db");
+    let mut conn = db::establish(db_path.to_str().unwrap()).unwrap();
+    db::migrate(&mut conn).unwrap();
+    let now = NaiveDateTime::parse_from_str("2020-01-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    (conn, Rc::new(Cell::new(now)))
+}
+
+fn local_context<'c, 'a>(
+    conn: &'c mut Connection,
+    db_path: &'a Path,
+    clock: &Rc<Cell<NaiveDateTime>>,
+) -> Context<'c, 'a> {
+    let clock = clock.clone();
+    let mut ctx = Context {
+        connection: conn,
+        db_path,
+        workspace_name: ichno::DEFAULT_WORKSPACE_NAME,
+        workspace: None,
+        group_name: ichno::DEFAULT_GROUP_NAME,
+        group: None,
+        timer: Box::new(move || Utc.from_utc_datetime(&clock.get())),
+    };
+    actions::pre_process(&mut ctx).unwrap();
+    ctx
+}
+
+fn scan_file(ctx: &mut Context, dir: &Path, path: &str, body: &str) -> ichno::Stat {
+    let file_path = dir.join(path);
+    fs::write(&file_path, body).unwrap();
+    let now = ctx.naive_current_time();
+    let group = ctx.group.as_ref().unwrap();
+    db::actions::update_stat_with_paths_if_needed(ctx.connection, group, path, &file_path, now).unwrap().unwrap()
+}
+
+#[test]
+fn test_train_content_dictionary() {
+    let (mut conn, now) = setup();
+    let encoder = Default::default();
+    for i in 0..200 {
+        let body = format!("{{\"id\": {}, \"name\": \"file-{}.txt\", \"tags\": [\"a\", \"b{}\"]}}", i, i * 7, i % 13);
+        let digest = db::actions::calc_digest(&mut body.as_bytes()).unwrap();
+        let footprint = db::actions::create_footprint_if_needed(&mut conn, &digest, body.len() as i64, 0, now).unwrap();
+        db::actions::create_content_if_needed(&mut conn, footprint.id, body.as_bytes(), None, &encoder, now).unwrap();
+    }
+    let dictionary = db::actions::train_content_dictionary(&mut conn, 4096, 1024, now).unwrap().unwrap();
+    assert_eq!(ContentCodec::Dictionary, dictionary.codec);
+    let retrained = db::actions::train_content_dictionary(&mut conn, 4096, 1024, now).unwrap().unwrap();
+    assert_eq!(dictionary.id, retrained.id);
+
+    // A stored file with the same body as the dictionary is left as it is.
+    conn.batch_execute(&format!("UPDATE contents SET codec = 0 WHERE id = {}", dictionary.id)).unwrap();
+    let err = db::actions::train_content_dictionary(&mut conn, 4096, 1024, now).unwrap_err();
+    assert!(matches!(err, DomainError::Conflict(_)), "{:?}", err);
+    assert_eq!(
+        ContentCodec::Raw,
+        Contents::find_by_footprint_id(&mut conn, dictionary.footprint_id).unwrap().unwrap().codec
+    );
+}
+
+#[test]
+fn test_restore_file_into_removed_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let (mut conn, clock) = setup_local(dir.path());
+    let db_path = dir.path().join("ichno.db");
+    let mut ctx = local_context(&mut conn, &db_path, &clock);
+    fs::create_dir(dir.path().join("d")).unwrap();
+    let stat = scan_file(&mut ctx, dir.path(), "d/a.txt", "a");
+    let policy = ContentPolicy::new(1024, &[]).unwrap();
+    assert!(actions::update_file_content(&mut ctx, &policy, &stat, "d/a.txt").unwrap().is_some());
+
+    fs::remove_dir_all(dir.path().join("d")).unwrap();
+    let history = actions::restore_file(&mut ctx, "d/a.txt", None, &RestoreOptions::default()).unwrap();
+    assert_eq!(1, history.version);
+    assert_eq!("a", fs::read_to_string(dir.path().join("d/a.txt")).unwrap());
+}
+
+#[test]
+fn test_find_workspace_and_group_does_not_create_them() {
+    let dir = tempfile::tempdir().unwrap();
+    let (mut conn, clock) = setup_local(dir.path());
+    let db_path = dir.path().join("ichno.db");
+    let mut ctx = Context {
+        connection: &mut conn,
+        db_path: &db_path,
+        workspace_name: ichno::DEFAULT_WORKSPACE_NAME,
+        workspace: None,
+        group_name: ichno::DEFAULT_GROUP_NAME,
+        group: None,
+        timer: Box::new(move || Utc.from_utc_datetime(&clock.get())),
+    };
+    let err = actions::find_workspace_and_group(&mut ctx).unwrap_err();
+    assert!(matches!(err, DomainError::NotFound(_)), "{:?}", err);
+    assert!(Workspaces::find_by_name(ctx.connection, ichno::DEFAULT_WORKSPACE_NAME).unwrap().is_none());
+
+    actions::pre_process(&mut ctx).unwrap();
+    ctx.group = None;
+    actions::find_workspace_and_group(&mut ctx).unwrap();
+    assert_eq!(ichno::DEFAULT_GROUP_NAME, ctx.group.unwrap().name);
+}
+
+#[test]
+fn test_extract_file_attrs_after_attr_rm() {
+    let dir = tempfile::tempdir().unwrap();
+    let (mut conn, clock) = setup_local(dir.path());
+    let db_path = dir.path().join("ichno.db");
+    let mut ctx = local_context(&mut conn, &db_path, &clock);
+    let stat = scan_file(&mut ctx, dir.path(), "a.txt", "a\nb\n");
+    let policy =
+        ExtractPolicy { extractors: find_extractors(&["text".to_owned()]).unwrap(), encoder: Default::default() };
+
+    let attrs = actions::extract_file_attrs(&mut ctx, &policy, &stat, "a.txt").unwrap();
+    assert_eq!(vec!["lines"], attrs.iter().map(|a| a.key.as_str()).collect::<Vec<_>>());
+    assert!(actions::extract_file_attrs(&mut ctx, &policy, &stat, "a.txt").unwrap().is_empty());
+
+    actions::delete_file_attr(&mut ctx, "a.txt", "lines").unwrap();
+    assert!(actions::list_file_attrs(&mut ctx, "a.txt").unwrap().is_empty());
+
+    let attrs = actions::extract_file_attrs(&mut ctx, &policy, &stat, "a.txt").unwrap();
+    assert_eq!(1, attrs.len());
+    let (attr, body) = actions::find_file_attr(&mut ctx, "a.txt", "lines").unwrap();
+    assert_eq!(Status::Enabled, attr.status);
+    assert_eq!(b"2".to_vec(), body);
+}
+
+#[test]
+fn test_attr_rm_and_get_at() {
+    let dir = tempfile::tempdir().unwrap();
+    let (mut conn, clock) = setup_local(dir.path());
+    let db_path = dir.path().join("ichno.db");
+    let mut ctx = local_context(&mut conn, &db_path, &clock);
+    scan_file(&mut ctx, dir.path(), "a.txt", "a\n");
+    let t0 = clock.get();
+    let later = |hours| t0 + chrono::Duration::hours(hours);
+
+    actions::set_file_attr(&mut ctx, "a.txt", "rating", b"1", ContentType::Json).unwrap();
+    clock.set(later(1));
+    actions::set_file_attr(&mut ctx, "a.txt", "rating", b"2", ContentType::Json).unwrap();
+    clock.set(later(2));
+    let attr = actions::delete_file_attr(&mut ctx, "a.txt", "rating").unwrap();
+    assert_eq!(Status::Disabled, attr.status);
+
+    assert!(actions::find_file_attr(&mut ctx, "a.txt", "rating").is_err());
+    assert!(actions::list_file_attrs(&mut ctx, "a.txt").unwrap().is_empty());
+    assert_eq!("key", actions::delete_file_attr(&mut ctx, "a.txt", "rating").unwrap_err().code());
+
+    let histories = actions::select_file_attr_histories(&mut ctx, "a.txt", "rating").unwrap();
+    let bodies: Vec<_> = histories.into_iter().map(|(_, body)| body).collect();
+    assert_eq!(vec![Some(b"1".to_vec()), Some(b"2".to_vec()), None], bodies);
+
+    let value_at = |ctx: &mut Context, at| actions::find_file_attr_at(ctx, "a.txt", "rating", at).map(|(_, body)| body);
+    assert!(value_at(&mut ctx, later(-1)).is_err());
+    assert_eq!(b"1".to_vec(), value_at(&mut ctx, t0).unwrap());
+    assert_eq!(b"1".to_vec(), value_at(&mut ctx, t0 + chrono::Duration::minutes(30)).unwrap());
+    assert_eq!(b"2".to_vec(), value_at(&mut ctx, later(1)).unwrap());
+    assert!(value_at(&mut ctx, later(3)).is_err());
+}
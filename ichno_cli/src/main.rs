@@ -1,20 +1,30 @@
 #[macro_use]
 extern crate log;
 
-use std::{collections::HashSet, env, error::Error, ffi::OsStr, path::Path, process::exit};
+use std::{
+    collections::HashSet,
+    env,
+    ffi::OsStr,
+    fs::File,
+    io::{stdout, BufWriter, Write},
+    path::{Path, PathBuf},
+    process::exit,
+};
 
-use chrono::Utc;
-use diesel::{connection::Connection, sqlite::SqliteConnection};
-use dotenv;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use diesel::{connection::Connection as _, sqlite::SqliteConnection};
 use ichno::{
     actions,
-    db::{SqliteStats, StatSearchCondition},
-    DEFAULT_GROUP_NAME, DEFAULT_WORKSPACE_NAME,
+    db::{Connection, StatSearchCondition, Stats},
+    error::{DomainError, DomainResult},
+    extract,
+    store::LocalContentStore,
+    ContentType, DEFAULT_GROUP_NAME, DEFAULT_WORKSPACE_NAME,
 };
-use ignore;
 use itertools::Itertools;
 use structopt::{clap, StructOpt};
 use twox_hash::RandomXxHashBuilder64;
+use url::Url;
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "ichno")]
@@ -28,6 +38,13 @@ pub struct Opt {
 #[derive(Debug, StructOpt)]
 pub enum SubCommands {
     Scan(Scan),
+    Cat(Cat),
+    Restore(Restore),
+    Recompress(Recompress),
+    Ls(Ls),
+    Attr(AttrCommands),
+    Push(Push),
+    Export(Export),
 }
 
 #[derive(Debug, StructOpt)]
@@ -37,14 +54,199 @@ pub struct Scan {
 
     #[structopt(long, default_value = "100", name = "N")]
     pub commit_interval: usize,
+
+    #[structopt(long)]
+    pub store_contents: bool,
+
+    #[structopt(long, default_value = "65536", name = "BYTES")]
+    pub store_contents_size_max: i64,
+
+    #[structopt(long, name = "GLOB", number_of_values = 1)]
+    pub store_contents_glob: Vec<String>,
+
+    #[structopt(long, name = "STORE_DIR")]
+    pub content_store: Option<String>,
+
+    #[structopt(long)]
+    pub content_store_compression: bool,
+
+    #[structopt(long, default_value = "3", name = "LEVEL")]
+    pub compression_level: i32,
+
+    #[structopt(long)]
+    pub extract_attrs: bool,
+
+    #[structopt(long, name = "EXTRACTOR", number_of_values = 1)]
+    pub extractor: Vec<String>,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct Cat {
+    #[structopt(name = "PATH[@VERSION]")]
+    pub path: String,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct Restore {
+    #[structopt(name = "PATH[@VERSION]")]
+    pub path: String,
+
+    #[structopt(long, name = "DEST")]
+    pub to: Option<String>,
+
+    #[structopt(short, long)]
+    pub force: bool,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct Ls {
+    #[structopt(name = "PATH_PREFIX")]
+    pub path_prefix: Option<String>,
+
+    #[structopt(long, name = "TIME", parse(try_from_str = parse_time))]
+    pub at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct Recompress {
+    #[structopt(long, default_value = "3", name = "LEVEL")]
+    pub level: i32,
+
+    #[structopt(long)]
+    pub train_dictionary: bool,
+
+    #[structopt(long, default_value = "112640", name = "BYTES")]
+    pub dictionary_size: usize,
+
+    #[structopt(long, default_value = "65536", name = "SAMPLE_BYTES")]
+    pub sample_size_max: usize,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct Push {
+    #[structopt(name = "SERVER_URL")]
+    pub server_url: Url,
+
+    #[structopt(short, long, name = "GROUP")]
+    pub group: String,
+
+    #[structopt(short, long, default_value = "default", name = "WORKSPACE")]
+    pub workspace: String,
+
+    #[structopt(long, env = "ICHNO_PUSH_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+
+    #[structopt(long, name = "N")]
+    pub batch_size: Option<i64>,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct Export {
+    #[structopt(short, long, name = "FILE", parse(from_os_str))]
+    pub output: Option<PathBuf>,
+
+    #[structopt(long, name = "HISTORY_ID")]
+    pub since: Option<i32>,
+
+    #[structopt(long, name = "N")]
+    pub batch_size: Option<i64>,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum AttrCommands {
+    Set(AttrSet),
+    Get(AttrGet),
+    List(AttrList),
+    Find(AttrFind),
+    Rm(AttrRm),
+    History(AttrHistory),
+}
+
+#[derive(Debug, StructOpt)]
+pub struct AttrSet {
+    #[structopt(name = "PATH")]
+    pub path: String,
+
+    #[structopt(name = "KEY")]
+    pub key: String,
+
+    #[structopt(name = "VALUE")]
+    pub value: String,
+
+    #[structopt(long)]
+    pub json: bool,
+
+    #[structopt(long, conflicts_with = "json")]
+    pub text: bool,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct AttrGet {
+    #[structopt(name = "PATH")]
+    pub path: String,
+
+    #[structopt(name = "KEY")]
+    pub key: String,
+
+    #[structopt(long, name = "TIME", parse(try_from_str = parse_time))]
+    pub at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct AttrRm {
+    #[structopt(name = "PATH")]
+    pub path: String,
+
+    #[structopt(name = "KEY")]
+    pub key: String,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct AttrHistory {
+    #[structopt(name = "PATH")]
+    pub path: String,
+
+    #[structopt(name = "KEY")]
+    pub key: String,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct AttrList {
+    #[structopt(name = "PATH")]
+    pub path: String,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct AttrFind {
+    #[structopt(name = "KEY")]
+    pub key: String,
+
+    #[structopt(name = "SUMMARY")]
+    pub summary: String,
+}
+
+fn parse_time(s: &str) -> Result<NaiveDateTime, chrono::ParseError> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.naive_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f"))
+        .or_else(|_| NaiveDate::parse_from_str(s, "%Y-%m-%d").map(|d| d.and_time(NaiveTime::MIN)))
+}
+
+fn split_version(s: &str) -> (&str, Option<i32>) {
+    if let Some((path, version)) = s.rsplit_once('@') {
+        if let Ok(version) = version.parse() {
+            return (path, Some(version));
+        }
+    }
+    (s, None)
 }
 
-fn main_with_error() -> Result<i32, Box<dyn Error>> {
+fn main_with_error() -> DomainResult<i32> {
     dotenv::dotenv().ok();
     env_logger::init();
 
     let database_url = env::var("DATABASE_URL").unwrap_or("ichno.db".to_owned());
-    let mut conn = SqliteConnection::establish(&database_url).expect(&format!("Error connecting to {}", database_url));
+    let mut conn = SqliteConnection::establish(&database_url).map(Connection::Sqlite)?;
     let db_path = Path::new(&database_url).canonicalize()?;
 
     ichno::db::migrate(&mut conn)?;
@@ -61,15 +263,33 @@ fn main_with_error() -> Result<i32, Box<dyn Error>> {
                 workspace: None,
                 group_name,
                 group: None,
-                timer: Box::new(|| Utc::now()),
+                timer: Box::new(Utc::now),
             };
-            let path = Path::new(scan.partial.as_ref().map(|s| s.as_str()).unwrap_or("."));
+            let path = Path::new(scan.partial.as_deref().unwrap_or("."));
             let w = {
-                let mut wb = ignore::WalkBuilder::new(&path);
+                let mut wb = ignore::WalkBuilder::new(path);
                 wb.filter_entry(|p| p.file_name() != OsStr::new(".git") && p.file_name() != OsStr::new("ichno.db"));
                 wb.build()
             };
             actions::pre_process(&mut ctx)?;
+            let content_policy = if scan.store_contents {
+                let mut policy = actions::ContentPolicy::new(scan.store_contents_size_max, &scan.store_contents_glob)?;
+                if let Some(dir) = scan.content_store.as_ref() {
+                    policy.store = Some(Box::new(LocalContentStore::new(dir, scan.content_store_compression)?));
+                }
+                policy.encoder = actions::load_content_encoder(&mut ctx, scan.compression_level)?;
+                Some(policy)
+            } else {
+                None
+            };
+            let extract_policy = if scan.extract_attrs {
+                Some(actions::ExtractPolicy {
+                    extractors: extract::find_extractors(&scan.extractor)?,
+                    encoder: actions::load_content_encoder(&mut ctx, scan.compression_level)?,
+                })
+            } else {
+                None
+            };
             let workspace = ctx.workspace.as_ref().unwrap();
             let workspace_id = workspace.id;
             let group = ctx.group.as_ref().unwrap();
@@ -77,7 +297,7 @@ fn main_with_error() -> Result<i32, Box<dyn Error>> {
             let mut path_set: HashSet<_, RandomXxHashBuilder64> = Default::default();
             let commit_interval = scan.commit_interval;
             for result_chunk in &w.chunks(commit_interval) {
-                ctx.connection.transaction::<_, Box<dyn Error>, _>(|conn| {
+                ctx.connection.transaction::<_, DomainError, _>(|conn| {
                     let mut new_ctx = actions::Context {
                         connection: conn,
                         db_path: &db_path,
@@ -85,7 +305,7 @@ fn main_with_error() -> Result<i32, Box<dyn Error>> {
                         workspace: Some(workspace.clone()),
                         group_name,
                         group: Some(group.clone()),
-                        timer: Box::new(|| Utc::now()),
+                        timer: Box::new(Utc::now),
                     };
                     for result in result_chunk {
                         match result {
@@ -94,6 +314,26 @@ fn main_with_error() -> Result<i32, Box<dyn Error>> {
                                     debug!("present: {:?}", entry.path());
                                     match actions::update_file_stat(&mut new_ctx, entry.path()) {
                                         Ok(Some(stat)) => {
+                                            if let Some(policy) = content_policy.as_ref() {
+                                                if let Err(e) = actions::update_file_content(
+                                                    &mut new_ctx,
+                                                    policy,
+                                                    &stat,
+                                                    entry.path(),
+                                                ) {
+                                                    warn!("{}", e);
+                                                }
+                                            }
+                                            if let Some(policy) = extract_policy.as_ref() {
+                                                if let Err(e) = actions::extract_file_attrs(
+                                                    &mut new_ctx,
+                                                    policy,
+                                                    &stat,
+                                                    entry.path(),
+                                                ) {
+                                                    warn!("{}", e);
+                                                }
+                                            }
                                             path_set.insert(stat.path);
                                         }
                                         Ok(None) => {}
@@ -111,7 +351,7 @@ fn main_with_error() -> Result<i32, Box<dyn Error>> {
             }
             let path_prefix =
                 if scan.partial.is_some() { path.strip_prefix(".").ok().unwrap_or(path).to_str() } else { None };
-            let stats = SqliteStats::search(
+            let stats = Stats::search(
                 ctx.connection,
                 workspace_id,
                 &StatSearchCondition {
@@ -122,7 +362,7 @@ fn main_with_error() -> Result<i32, Box<dyn Error>> {
                 },
             )?;
             for stat_chunk in &stats.iter().chunks(commit_interval) {
-                ctx.connection.transaction::<_, Box<dyn Error>, _>(|conn| {
+                ctx.connection.transaction::<_, DomainError, _>(|conn| {
                     let mut new_ctx = actions::Context {
                         connection: conn,
                         db_path: &db_path,
@@ -130,7 +370,7 @@ fn main_with_error() -> Result<i32, Box<dyn Error>> {
                         workspace: Some(workspace.clone()),
                         group_name,
                         group: Some(group.clone()),
-                        timer: Box::new(|| Utc::now()),
+                        timer: Box::new(Utc::now),
                     };
                     for stat in stat_chunk {
                         if path_set.contains(&stat.path) {
@@ -147,6 +387,190 @@ fn main_with_error() -> Result<i32, Box<dyn Error>> {
             }
             actions::post_process(&mut ctx)?;
         }
+        SubCommands::Cat(cat) => {
+            let mut ctx = actions::Context {
+                connection: &mut conn,
+                db_path: &db_path,
+                workspace_name,
+                workspace: None,
+                group_name,
+                group: None,
+                timer: Box::new(Utc::now),
+            };
+            actions::find_workspace_and_group(&mut ctx)?;
+            let (path, version) = split_version(&cat.path);
+            let (_, body) = actions::find_file_content(&mut ctx, path, version)?;
+            stdout().lock().write_all(&body)?;
+        }
+        SubCommands::Restore(restore) => {
+            let mut ctx = actions::Context {
+                connection: &mut conn,
+                db_path: &db_path,
+                workspace_name,
+                workspace: None,
+                group_name,
+                group: None,
+                timer: Box::new(Utc::now),
+            };
+            actions::find_workspace_and_group(&mut ctx)?;
+            let (path, version) = split_version(&restore.path);
+            let options =
+                actions::RestoreOptions { destination: restore.to.as_ref().map(Path::new), force: restore.force };
+            actions::restore_file(&mut ctx, path, version, &options)?;
+        }
+        SubCommands::Ls(ls) => {
+            let mut ctx = actions::Context {
+                connection: &mut conn,
+                db_path: &db_path,
+                workspace_name,
+                workspace: None,
+                group_name,
+                group: None,
+                timer: Box::new(Utc::now),
+            };
+            actions::pre_process(&mut ctx)?;
+            let at = ls.at.unwrap_or_else(|| ctx.naive_current_time());
+            for history in actions::list_files_at(&mut ctx, ls.path_prefix.as_deref(), at)? {
+                println!("{}\t{}", history.digest.as_deref().unwrap_or("-"), &history.path);
+            }
+        }
+        SubCommands::Recompress(recompress) => {
+            let options = actions::RecompressOptions {
+                level: recompress.level,
+                train_dictionary: recompress.train_dictionary,
+                dictionary_size: recompress.dictionary_size,
+                sample_size_max: recompress.sample_size_max,
+            };
+            conn.transaction::<_, DomainError, _>(|conn| {
+                let mut ctx = actions::Context {
+                    connection: conn,
+                    db_path: &db_path,
+                    workspace_name,
+                    workspace: None,
+                    group_name,
+                    group: None,
+                    timer: Box::new(Utc::now),
+                };
+                let (count, updated_count) = actions::recompress(&mut ctx, &options)?;
+                println!("{} / {} contents recompressed", updated_count, count);
+                Ok(())
+            })?;
+        }
+        SubCommands::Push(push) => {
+            let mut ctx = actions::Context {
+                connection: &mut conn,
+                db_path: &db_path,
+                workspace_name,
+                workspace: None,
+                group_name,
+                group: None,
+                timer: Box::new(Utc::now),
+            };
+            actions::pre_process(&mut ctx)?;
+            let options = actions::PushOptions {
+                server_url: push.server_url,
+                workspace_name: push.workspace,
+                group_name: push.group.clone(),
+                token: push.token,
+                batch_size: push.batch_size,
+            };
+            let result = actions::push(&mut ctx, &options)?;
+            println!(
+                "{}: {} histories, {} stats, {} footprints",
+                &push.group, result.histories, result.stats, result.footprints
+            );
+        }
+        SubCommands::Export(export) => {
+            let mut ctx = actions::Context {
+                connection: &mut conn,
+                db_path: &db_path,
+                workspace_name,
+                workspace: None,
+                group_name,
+                group: None,
+                timer: Box::new(Utc::now),
+            };
+            actions::pre_process(&mut ctx)?;
+            let options = actions::ExportOptions { since: export.since, batch_size: export.batch_size };
+            let footer = if let Some(output) = export.output.as_ref() {
+                actions::export(&mut ctx, &options, &mut BufWriter::new(File::create(output)?))?
+            } else {
+                actions::export(&mut ctx, &options, &mut BufWriter::new(stdout().lock()))?
+            };
+            info!("{} histories exported (last history id: {:?})", footer.histories, footer.last_history_id);
+        }
+        SubCommands::Attr(attr) => {
+            let mut ctx = actions::Context {
+                connection: &mut conn,
+                db_path: &db_path,
+                workspace_name,
+                workspace: None,
+                group_name,
+                group: None,
+                timer: Box::new(Utc::now),
+            };
+            actions::pre_process(&mut ctx)?;
+            match attr {
+                AttrCommands::Set(set) => {
+                    let content_type = if set.json { ContentType::Json } else { ContentType::Text };
+                    ctx.connection.transaction::<_, DomainError, _>(|conn| {
+                        let mut new_ctx = actions::Context {
+                            connection: conn,
+                            db_path: &db_path,
+                            workspace_name,
+                            workspace: ctx.workspace.clone(),
+                            group_name,
+                            group: ctx.group.clone(),
+                            timer: Box::new(Utc::now),
+                        };
+                        actions::set_file_attr(&mut new_ctx, &set.path, &set.key, set.value.as_bytes(), content_type)?;
+                        Ok(())
+                    })?;
+                }
+                AttrCommands::Get(get) => {
+                    let body = if let Some(at) = get.at {
+                        actions::find_file_attr_at(&mut ctx, &get.path, &get.key, at)?.1
+                    } else {
+                        actions::find_file_attr(&mut ctx, &get.path, &get.key)?.1
+                    };
+                    stdout().lock().write_all(&body)?;
+                }
+                AttrCommands::Rm(rm) => {
+                    ctx.connection.transaction::<_, DomainError, _>(|conn| {
+                        let mut new_ctx = actions::Context {
+                            connection: conn,
+                            db_path: &db_path,
+                            workspace_name,
+                            workspace: ctx.workspace.clone(),
+                            group_name,
+                            group: ctx.group.clone(),
+                            timer: Box::new(Utc::now),
+                        };
+                        actions::delete_file_attr(&mut new_ctx, &rm.path, &rm.key)?;
+                        Ok(())
+                    })?;
+                }
+                AttrCommands::History(history) => {
+                    for (h, body) in actions::select_file_attr_histories(&mut ctx, &history.path, &history.key)? {
+                        let value = match body.as_ref() {
+                            Some(body) => String::from_utf8_lossy(body).escape_debug().to_string(),
+                            None => "-".to_owned(),
+                        };
+                        println!("{}\t{}\t{}", h.version, h.created_at.format("%Y-%m-%dT%H:%M:%S%.f"), value);
+                    }
+                }
+                AttrCommands::List(list) => {
+                    for attr in actions::list_file_attrs(&mut ctx, &list.path)? {
+                        println!("{}\t{}", &attr.key, attr.value_summary.as_deref().unwrap_or(""));
+                    }
+                }
+                AttrCommands::Find(find) => {
+                    for stat in actions::find_files_by_attr(&mut ctx, &find.key, &find.summary)? {
+                        println!("{}", &stat.path);
+                    }
+                }
+            }
+        }
     };
     Ok(0)
 }
@@ -154,6 +578,9 @@ fn main_with_error() -> Result<i32, Box<dyn Error>> {
 fn main() {
     match main_with_error() {
         Ok(code) => exit(code),
-        Err(e) => error!("{}", e),
+        Err(e) => {
+            error!("{}", e);
+            exit(e.exit_code())
+        }
     }
 }
@@ -0,0 +1,5 @@
+DROP INDEX `ix_contents_codec_id` ON `contents`;
+
+ALTER TABLE `contents` DROP FOREIGN KEY `fk_contents_dictionary_id`;
+ALTER TABLE `contents` DROP COLUMN `dictionary_id`;
+ALTER TABLE `contents` DROP COLUMN `codec`;
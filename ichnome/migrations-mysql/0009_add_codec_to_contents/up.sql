@@ -0,0 +1,6 @@
+ALTER TABLE `contents` ADD COLUMN `codec` INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE `contents` ADD COLUMN `dictionary_id` INTEGER;  -- FK
+
+ALTER TABLE `contents` ADD CONSTRAINT `fk_contents_dictionary_id` FOREIGN KEY (`dictionary_id`) REFERENCES `contents` (`id`);
+
+CREATE INDEX `ix_contents_codec_id` ON `contents` (`codec`, `id`);
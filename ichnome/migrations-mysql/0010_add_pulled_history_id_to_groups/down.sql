@@ -0,0 +1 @@
+ALTER TABLE `groups` DROP COLUMN `pulled_history_id`;
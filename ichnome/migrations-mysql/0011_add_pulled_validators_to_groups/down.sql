@@ -0,0 +1,2 @@
+ALTER TABLE `groups` DROP COLUMN `pulled_last_modified`;
+ALTER TABLE `groups` DROP COLUMN `pulled_etag`;
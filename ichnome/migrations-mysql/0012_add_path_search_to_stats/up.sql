@@ -0,0 +1 @@
+CREATE FULLTEXT INDEX `ix_stats_path_fulltext` ON `stats` (`path`) WITH PARSER ngram;
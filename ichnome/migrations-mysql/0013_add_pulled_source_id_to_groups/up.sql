@@ -0,0 +1 @@
+ALTER TABLE `groups` ADD COLUMN `pulled_source_id` VARCHAR(64);  -- the identity of the remote DB last pulled
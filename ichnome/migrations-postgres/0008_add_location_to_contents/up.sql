@@ -0,0 +1 @@
+ALTER TABLE "contents" ADD COLUMN "location" varchar(512);  -- NULL if the body is stored in this table
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS "ix_contents_codec_id";
+
+ALTER TABLE "contents" DROP CONSTRAINT IF EXISTS "fk_contents_dictionary_id";
+ALTER TABLE "contents" DROP COLUMN IF EXISTS "dictionary_id";
+ALTER TABLE "contents" DROP COLUMN IF EXISTS "codec";
@@ -0,0 +1 @@
+ALTER TABLE "groups" ADD COLUMN "pulled_history_id" integer;  -- the last history ID imported from the remote group
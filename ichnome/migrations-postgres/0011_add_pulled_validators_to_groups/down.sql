@@ -0,0 +1,2 @@
+ALTER TABLE "groups" DROP COLUMN IF EXISTS "pulled_last_modified";
+ALTER TABLE "groups" DROP COLUMN IF EXISTS "pulled_etag";
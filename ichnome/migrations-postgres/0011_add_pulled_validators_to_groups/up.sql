@@ -0,0 +1,2 @@
+ALTER TABLE "groups" ADD COLUMN "pulled_etag" varchar(512);  -- the ETag of the remote DB last pulled
+ALTER TABLE "groups" ADD COLUMN "pulled_last_modified" varchar(64);  -- the Last-Modified of the remote DB last pulled
@@ -0,0 +1 @@
+DROP INDEX "ix_stats_path_trgm";
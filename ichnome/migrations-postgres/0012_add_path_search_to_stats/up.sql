@@ -0,0 +1,3 @@
+CREATE EXTENSION IF NOT EXISTS "pg_trgm";
+
+CREATE INDEX "ix_stats_path_trgm" ON "stats" USING gin ("path" gin_trgm_ops);
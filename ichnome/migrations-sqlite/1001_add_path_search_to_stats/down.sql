@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS `tr_stats_fts_update`;
+DROP TRIGGER IF EXISTS `tr_stats_fts_delete`;
+DROP TRIGGER IF EXISTS `tr_stats_fts_insert`;
+DROP TABLE IF EXISTS `stats_fts`;
@@ -0,0 +1,21 @@
+CREATE VIRTUAL TABLE IF NOT EXISTS `stats_fts` USING fts5(
+    `path`,
+    content = 'stats',
+    content_rowid = 'id',
+    tokenize = "unicode61 separators '/_.'"
+);
+
+INSERT INTO `stats_fts` (`stats_fts`) VALUES ('rebuild');
+
+CREATE TRIGGER IF NOT EXISTS `tr_stats_fts_insert` AFTER INSERT ON `stats` BEGIN
+    INSERT INTO `stats_fts` (`rowid`, `path`) VALUES (new.`id`, new.`path`);
+END;
+
+CREATE TRIGGER IF NOT EXISTS `tr_stats_fts_delete` AFTER DELETE ON `stats` BEGIN
+    INSERT INTO `stats_fts` (`stats_fts`, `rowid`, `path`) VALUES ('delete', old.`id`, old.`path`);
+END;
+
+CREATE TRIGGER IF NOT EXISTS `tr_stats_fts_update` AFTER UPDATE OF `path` ON `stats` BEGIN
+    INSERT INTO `stats_fts` (`stats_fts`, `rowid`, `path`) VALUES ('delete', old.`id`, old.`path`);
+    INSERT INTO `stats_fts` (`rowid`, `path`) VALUES (new.`id`, new.`path`);
+END;
@@ -1,26 +1,46 @@
-use std::{error::Error, path::Path};
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::BufRead,
+    path::Path,
+    time::Duration,
+};
 
 use chrono::{DateTime, NaiveDateTime, Utc};
-use diesel::{Connection, SqliteConnection};
-use ichno::db::{SqliteFootprints, SqliteGroups, SqliteHistories, SqliteStats, SqliteWorkspaces};
-
+use diesel::{Connection as _, SqliteConnection};
+use ichno::{
+    export::{read_record, Footer, Record},
+    push::{Changeset, PushCursor},
+    store,
+};
 use url::Url;
 
 use crate::{
-    constants::GroupType,
+    codec::{self, ContentEncoder, DEFAULT_LEVEL},
+    constants::{GroupType, Status, ATTR_GROUP_NAME, META_GROUP_NAME},
     db::{
         actions::{
-            create_footprint_if_needed, create_group_if_needed, create_meta_group_if_needed,
-            create_workspace_if_needed, new_updated_file_state_if_needed, update_meta_group_stat, FileState,
+            calc_digest, create_attr_and_stat_with_bytes_if_needed, create_footprint_if_needed, create_group_if_needed,
+            create_meta_group_if_needed, create_workspace_if_needed, disable_attr_and_stat_if_needed,
+            find_content_encoder, is_source_recreated, new_updated_file_state_if_needed, source_id,
+            update_meta_group_stat, AttrValue, FileState,
         },
-        Connection as OmConnection, OmFootprints, OmGroups, OmHistories, OmStats, OmWorkspaces,
+        Connection as OmConnection, OmAttrs, OmContents, OmFootprints, OmGroups, OmHistories, OmStats, OmWorkspaces,
+        StatSearchCondition,
     },
+    error::{DomainError, DomainResult},
+    http::{self, Download, Validators},
     models::{
-        Group, GroupUpdateForm, HistoryInsertForm, StatInsertForm, StatUpdateForm, Workspace, WorkspaceUpdateForm,
+        Content, Footprint, FootprintInsertForm, Group, GroupUpdateForm, HistoryInsertForm, Stat, StatInsertForm,
+        StatUpdateForm, Workspace, WorkspaceUpdateForm,
     },
-    ssh,
+    s3,
+    source::{ChangesetSource, LocalSource, SqliteSource},
+    ssh::{self, SshOptions},
 };
 
+const PULL_BATCH_SIZE: i64 = 5000;
+const PULL_SCHEMES: &[&str] = &["file", "ssh", "http", "https", "s3"];
+
 pub struct Context<'c> {
     pub connection: &'c mut OmConnection,
     pub timer: Box<dyn Fn() -> DateTime<Utc>>,
@@ -36,6 +56,30 @@ impl<'c> Context<'c> {
     }
 }
 
+pub fn select_content_bodies(conn: &mut OmConnection, footprint_ids: &[i32]) -> DomainResult<HashMap<i32, Vec<u8>>> {
+    let contents = OmContents::select_by_footprint_ids(conn, footprint_ids)?;
+    let dictionary_ids: Vec<i32> =
+        contents.iter().filter_map(|c| c.dictionary_id).collect::<BTreeSet<_>>().into_iter().collect();
+    let dictionaries: HashMap<i32, Content> =
+        OmContents::select(conn, &dictionary_ids)?.into_iter().map(|c| (c.id, c)).collect();
+    let mut bodies = HashMap::new();
+    for content in contents.iter() {
+        let body = if let Some(location) = content.location.as_ref() {
+            store::load(location)?
+        } else {
+            let dictionary = match content.dictionary_id {
+                Some(dictionary_id) => Some(dictionaries.get(&dictionary_id).ok_or_else(|| {
+                    DomainError::internal("dictionary_id", format!("dictionary is not found: {}", dictionary_id))
+                })?),
+                None => None,
+            };
+            codec::decode(content.codec, &content.body, dictionary.map(|d| d.body.as_slice()))?
+        };
+        bodies.insert(content.footprint_id, body);
+    }
+    Ok(bodies)
+}
+
 #[derive(Debug)]
 pub struct SetupRequest {
     pub workspace_name: String,
@@ -53,7 +97,7 @@ pub struct SetupResponse {
     pub workspace: Workspace,
 }
 
-pub fn setup(ctx: &mut Context, req: &SetupRequest) -> Result<SetupResponse, Box<dyn Error>> {
+pub fn setup(ctx: &mut Context, req: &SetupRequest) -> DomainResult<SetupResponse> {
     let now = ctx.naive_current_time();
     let workspace = OmWorkspaces::find_by_name(ctx.connection, &req.workspace_name)?;
     if let Some(workspace) = workspace {
@@ -61,13 +105,13 @@ pub fn setup(ctx: &mut Context, req: &SetupRequest) -> Result<SetupResponse, Box
             OmWorkspaces::update_and_find(
                 ctx.connection,
                 workspace.id,
-                &WorkspaceUpdateForm {
-                    description: req.options.description.as_ref().map(|s| s.as_str()),
-                    ..Default::default()
-                },
+                &WorkspaceUpdateForm { description: req.options.description.as_deref(), ..Default::default() },
             )?;
         } else {
-            panic!("workspae {} already exists", workspace.name)
+            return Err(DomainError::already_exists(
+                "workspace",
+                format!("workspace already exists: {}", workspace.name),
+            ));
         }
     }
     let workspace = create_workspace_if_needed(ctx.connection, &req.workspace_name, now)?;
@@ -94,10 +138,13 @@ pub struct RegisterResponse {
     pub group: Group,
 }
 
-pub fn register(ctx: &mut Context, req: &RegisterRequest) -> Result<RegisterResponse, Box<dyn Error>> {
+pub fn register(ctx: &mut Context, req: &RegisterRequest) -> DomainResult<RegisterResponse> {
     let now = ctx.naive_current_time();
-    let workspace = OmWorkspaces::find_by_name(ctx.connection, &req.workspace_name)?.unwrap();
+    let workspace = find_workspace(ctx.connection, &req.workspace_name)?;
     let url = Url::parse(&req.url)?;
+    if !PULL_SCHEMES.contains(&url.scheme()) {
+        return Err(unsupported_scheme_error(url.scheme()));
+    }
     let group = OmGroups::find_by_name(ctx.connection, workspace.id, &req.group_name)?;
     if let Some(group) = group {
         if req.options.force {
@@ -106,12 +153,16 @@ pub fn register(ctx: &mut Context, req: &RegisterRequest) -> Result<RegisterResp
                 group.id,
                 &GroupUpdateForm {
                     url: Some(&req.url),
-                    description: req.options.description.as_ref().map(|s| s.as_str()),
+                    description: req.options.description.as_deref(),
+                    pulled_history_id: Some(None),
+                    pulled_etag: Some(None),
+                    pulled_last_modified: Some(None),
+                    pulled_source_id: Some(None),
                     ..Default::default()
                 },
             )?;
         } else {
-            panic!("group {} already exists", group.name)
+            return Err(DomainError::already_exists("group", format!("group already exists: {}", group.name)));
         }
     }
     let group = create_group_if_needed(ctx.connection, &workspace, &req.group_name, &url, GroupType::Remote, now)?;
@@ -125,45 +176,329 @@ pub struct PullRequest {
     pub options: PullOptions,
 }
 
-#[derive(Debug)]
-pub struct PullOptions {}
+#[derive(Default, Debug)]
+pub struct PullOptions {
+    pub batch_size: Option<i64>,
+    pub force: bool,
+    pub timeout: Option<Duration>,
+    pub ssh: SshOptions,
+}
 
-impl Default for PullOptions {
-    fn default() -> Self {
-        PullOptions {}
+#[derive(Default, Debug, Clone, Copy)]
+pub struct PullCounts {
+    pub histories: usize,
+    pub stats: usize,
+    pub footprints: usize,
+    pub attrs: usize,
+}
+
+impl PullCounts {
+    fn add(&mut self, other: &PullCounts) {
+        self.histories += other.histories;
+        self.stats += other.stats;
+        self.footprints += other.footprints;
+        self.attrs += other.attrs;
     }
 }
 
 #[derive(Debug)]
 pub struct PullResponse {
     pub group: Group,
+    pub counts: PullCounts,
+}
+
+fn unsupported_scheme_error(scheme: &str) -> DomainError {
+    DomainError::unsupported_scheme("url", format!("unsupported scheme: {}", scheme))
 }
 
-pub fn pull(ctx: &mut Context, req: &PullRequest) -> Result<PullResponse, Box<dyn Error>> {
-    let workspace = OmWorkspaces::find_by_name(ctx.connection, &req.workspace_name)?.unwrap();
-    let group = OmGroups::find_by_name(ctx.connection, workspace.id, &req.group_name)?.unwrap();
+fn find_workspace(conn: &mut OmConnection, workspace_name: &str) -> DomainResult<Workspace> {
+    OmWorkspaces::find_by_name(conn, workspace_name)?
+        .ok_or_else(|| DomainError::not_found("workspace", format!("workspace is not found: {}", workspace_name)))
+}
+
+fn find_group(conn: &mut OmConnection, workspace: &Workspace, group_name: &str) -> DomainResult<Group> {
+    OmGroups::find_by_name(conn, workspace.id, group_name)?
+        .ok_or_else(|| DomainError::not_found("group", format!("group is not found: {}", group_name)))
+}
+
+pub fn pull(ctx: &mut Context, req: &PullRequest) -> DomainResult<PullResponse> {
+    let workspace = find_workspace(ctx.connection, &req.workspace_name)?;
+    let group = find_group(ctx.connection, &workspace, &req.group_name)?;
+    if group.type_ != GroupType::Remote {
+        return Err(DomainError::conflict("group", format!("group is not registered for pull: {}", group.name)));
+    }
     let url = Url::parse(&group.url)?;
     let scheme = url.scheme();
-    if scheme == "ssh" {
-        let tempfile = ssh::download(&url)?;
-        load_local_db(ctx, req, &workspace, &group, tempfile.path())?;
-        tempfile.close()?;
-    } else if scheme == "file" {
-        let path = Path::new(url.path());
-        load_local_db(ctx, req, &workspace, &group, path)?;
+    let counts = match scheme {
+        "file" => {
+            let path = Path::new(url.path());
+            load_local_db(ctx, req, &workspace, &group, path)?
+        }
+        "ssh" | "http" | "https" | "s3" => {
+            let validators = if req.options.force {
+                Validators::default()
+            } else {
+                Validators { etag: group.pulled_etag.clone(), last_modified: group.pulled_last_modified.clone() }
+            };
+            let timeout = req.options.timeout;
+            let download = match scheme {
+                "ssh" => ssh::download(&url, &validators, &req.options.ssh, timeout)?,
+                "s3" => s3::download(&url, &validators, timeout)?,
+                _ => http::download(&url, &validators, timeout)?,
+            };
+            match download {
+                Download::NotModified => {
+                    info!("remote db not modified: {}", &group.name);
+                    PullCounts::default()
+                }
+                Download::Modified { tempfile, validators } => {
+                    let counts = load_local_db(ctx, req, &workspace, &group, tempfile.path())?;
+                    tempfile.close()?;
+                    OmGroups::update(
+                        ctx.connection,
+                        group.id,
+                        &GroupUpdateForm {
+                            pulled_etag: Some(validators.etag.as_deref()),
+                            pulled_last_modified: Some(validators.last_modified.as_deref()),
+                            ..Default::default()
+                        },
+                    )?;
+                    counts
+                }
+            }
+        }
+        _ => return Err(unsupported_scheme_error(scheme)),
+    };
+    info!(
+        "pulled: {}: {} histories, {} stats, {} footprints, {} attrs",
+        &group.name, counts.histories, counts.stats, counts.footprints, counts.attrs
+    );
+    Ok(PullResponse { group, counts })
+}
+
+#[derive(Debug)]
+pub struct PushRequest {
+    pub workspace_name: String,
+    pub group_name: String,
+    pub changeset: Changeset,
+}
+
+#[derive(Debug)]
+pub struct PushResponse {
+    pub group: Group,
+    pub counts: PullCounts,
+}
+
+fn check_push_group_name(group_name: &str) -> DomainResult<()> {
+    if group_name == META_GROUP_NAME || group_name == ATTR_GROUP_NAME {
+        return Err(DomainError::params("group_name", format!("group name is reserved: {}", group_name)));
+    }
+    Ok(())
+}
+
+/// Pushed groups are fed by their pushers only, so that pulls never move their cursors and vice versa.
+fn check_push_group(group: &Group) -> DomainResult<()> {
+    if group.type_ != GroupType::Pushed {
+        return Err(DomainError::conflict("group", format!("group is not for push: {}", group.name)));
+    }
+    Ok(())
+}
+
+pub fn find_push_cursor(ctx: &mut Context, workspace_name: &str, group_name: &str) -> DomainResult<Option<PushCursor>> {
+    check_push_group_name(group_name)?;
+    let workspace = if let Some(workspace) = OmWorkspaces::find_by_name(ctx.connection, workspace_name)? {
+        workspace
     } else {
-        panic!("unknown scheme: {}", scheme);
+        return Ok(None);
+    };
+    let group = OmGroups::find_by_name(ctx.connection, workspace.id, group_name)?;
+    if let Some(group) = group.as_ref() {
+        check_push_group(group)?;
     }
-    Ok(PullResponse { group })
+    let (pulled_history_id, pulled_source_id) =
+        group.map(|g| (g.pulled_history_id, g.pulled_source_id)).unwrap_or_default();
+    Ok(Some(PushCursor { pulled_history_id, pulled_source_id }))
+}
+
+pub fn push(ctx: &mut Context, req: &PushRequest) -> DomainResult<PushResponse> {
+    let now = ctx.naive_current_time();
+    let changeset = &req.changeset;
+    check_push_group_name(&req.group_name)?;
+    let url = Url::parse(&changeset.url)?;
+    let workspace = find_workspace(ctx.connection, &req.workspace_name)?;
+    let (group, counts) = ctx.connection.transaction::<_, DomainError, _>(|conn| {
+        let mut new_ctx = Context { connection: conn, timer: Box::new(Utc::now) };
+        let group =
+            create_group_if_needed(new_ctx.connection, &workspace, &req.group_name, &url, GroupType::Pushed, now)?;
+        check_push_group(&group)?;
+        if group.pulled_history_id != changeset.from_history_id {
+            return Err(DomainError::conflict(
+                "pulled_history_id",
+                format!(
+                    "changeset is not based on the pushed histories: {:?} != {:?}",
+                    changeset.from_history_id, group.pulled_history_id
+                ),
+            ));
+        }
+        create_meta_group_if_needed(new_ctx.connection, &workspace, now)?;
+        let histories: Vec<&ichno::History> = changeset.histories.iter().collect();
+        let mut source = ChangesetSource { footprints: &changeset.footprints, ..Default::default() };
+        let counts = load_local_histories(&mut new_ctx, &workspace, &group, &mut source, &histories, now)?;
+        let group = if let Some(last_history) = changeset.histories.iter().max_by_key(|h| h.id) {
+            OmGroups::update_and_find(
+                new_ctx.connection,
+                group.id,
+                &GroupUpdateForm {
+                    pulled_history_id: Some(Some(last_history.id)),
+                    pulled_source_id: Some(Some(&changeset.source_id)),
+                    ..Default::default()
+                },
+            )?
+        } else {
+            group
+        };
+        Ok((group, counts))
+    })?;
+    info!(
+        "pushed: {}: {} histories, {} stats, {} footprints",
+        &group.name, counts.histories, counts.stats, counts.footprints
+    );
+    Ok(PushResponse { group, counts })
+}
+
+#[derive(Debug)]
+pub struct ImportRequest {
+    pub workspace_name: String,
+    pub group_name: String,
+}
+
+#[derive(Debug)]
+pub struct ImportResponse {
+    pub group: Option<Group>,
+    pub counts: PullCounts,
+    pub footer: Option<Footer>,
+}
+
+#[derive(Default)]
+struct ImportBatch {
+    footprints: Vec<ichno::Footprint>,
+    contents: HashMap<i32, Vec<u8>>,
+    attrs: Vec<ichno::Attr>,
+    histories: Vec<ichno::History>,
+}
+
+fn import_batch(
+    ctx: &mut Context,
+    glb_workspace: &Workspace,
+    glb_group: &Group,
+    loc_attr_group_id: Option<i32>,
+    batch: ImportBatch,
+    encoder: &ContentEncoder,
+) -> DomainResult<PullCounts> {
+    let now = ctx.naive_current_time();
+    let (loc_attr_histories, loc_file_histories): (Vec<_>, Vec<_>) =
+        batch.histories.iter().partition(|h| Some(h.group_id) == loc_attr_group_id);
+    let mut source = ChangesetSource {
+        footprints: &batch.footprints,
+        attrs: &batch.attrs,
+        histories: &batch.histories,
+        contents: batch.contents,
+    };
+    ctx.connection.transaction::<_, DomainError, _>(|conn| {
+        let mut new_ctx = Context { connection: conn, timer: Box::new(Utc::now) };
+        let mut counts =
+            load_local_histories(&mut new_ctx, glb_workspace, glb_group, &mut source, &loc_file_histories, now)?;
+        counts.attrs += load_local_attrs(&mut new_ctx, glb_workspace, &mut source, &loc_attr_histories, encoder, now)?;
+        Ok(counts)
+    })
+}
+
+pub fn import<R: BufRead>(ctx: &mut Context, req: &ImportRequest, r: &mut R) -> DomainResult<ImportResponse> {
+    let now = ctx.naive_current_time();
+    let workspace = find_workspace(ctx.connection, &req.workspace_name)?;
+    let mut line = String::new();
+    let loc_group_name = match read_record(r, &mut line)? {
+        Some(Record::Header(header)) => {
+            header.validate()?;
+            debug!("import header: {:?}", &header);
+            header.group_name.unwrap_or_else(|| ichno::DEFAULT_GROUP_NAME.to_owned())
+        }
+        _ => return Err(DomainError::params("header", "header is missing".to_owned())),
+    };
+    let encoder = find_content_encoder(ctx.connection, DEFAULT_LEVEL)?;
+    let mut group = None;
+    let mut loc_group_id = None;
+    let mut loc_attr_group_id = None;
+    let mut counts = PullCounts::default();
+    let mut batch = ImportBatch::default();
+    let mut footer = None;
+    loop {
+        let record = read_record(r, &mut line)?;
+        let flush = match record.as_ref() {
+            Some(Record::History(_)) => false,
+            Some(Record::Group(_)) | Some(Record::Workspace(_)) => false,
+            _ => !batch.histories.is_empty(),
+        };
+        if flush {
+            let glb_group = group
+                .as_ref()
+                .ok_or_else(|| DomainError::params("group", format!("group is missing: {}", &loc_group_name)))?;
+            let batch_counts =
+                import_batch(ctx, &workspace, glb_group, loc_attr_group_id, std::mem::take(&mut batch), &encoder)?;
+            debug!("histories imported: {:?}", &batch_counts);
+            counts.add(&batch_counts);
+        }
+        match record {
+            Some(Record::Header(_)) => {
+                return Err(DomainError::params("header", "header is duplicated".to_owned()));
+            }
+            Some(Record::Workspace(loc_workspace)) => debug!("import workspace: {}", &loc_workspace.name),
+            Some(Record::Group(loc_group)) => {
+                if loc_group.name == loc_group_name {
+                    let url = Url::parse(&loc_group.url)?;
+                    group = Some(ctx.connection.transaction::<_, DomainError, _>(|conn| {
+                        create_meta_group_if_needed(conn, &workspace, now)?;
+                        create_group_if_needed(conn, &workspace, &req.group_name, &url, GroupType::Remote, now)
+                    })?);
+                    loc_group_id = Some(loc_group.id);
+                } else if loc_group.name == ATTR_GROUP_NAME {
+                    loc_attr_group_id = Some(loc_group.id);
+                }
+            }
+            Some(Record::Footprint(footprint)) => batch.footprints.push(footprint),
+            Some(Record::Content(content)) => {
+                batch.contents.insert(content.footprint_id, content.decode_body()?);
+            }
+            Some(Record::Attr(attr)) => batch.attrs.push(attr),
+            Some(Record::History(history)) => {
+                if Some(history.group_id) == loc_group_id || Some(history.group_id) == loc_attr_group_id {
+                    batch.histories.push(history);
+                }
+            }
+            Some(Record::Footer(f)) => {
+                footer = Some(f);
+                break;
+            }
+            None => {
+                warn!("Footer is missing; the stream may be truncated");
+                break;
+            }
+        }
+    }
+    info!(
+        "imported: {}: {} histories, {} stats, {} footprints, {} attrs",
+        &req.group_name, counts.histories, counts.stats, counts.footprints, counts.attrs
+    );
+    Ok(ImportResponse { group, counts, footer })
 }
 
 fn load_local_db(
     ctx: &mut Context,
-    _req: &PullRequest,
+    req: &PullRequest,
     glb_workspace: &Workspace,
     glb_group: &Group,
     path: &Path,
-) -> Result<(), Box<dyn Error>> {
+) -> DomainResult<PullCounts> {
     let now = ctx.naive_current_time();
     let meta_group = create_meta_group_if_needed(ctx.connection, glb_workspace, now)?;
     let meta_stat = OmStats::find_by_path(ctx.connection, meta_group.id, &glb_group.name)?;
@@ -172,109 +507,320 @@ fn load_local_db(
     {
         updated_metadata
     } else {
-        return Ok(());
+        return Ok(Default::default());
     };
 
-    let mut loc_conn = SqliteConnection::establish(path.to_str().unwrap())?;
+    let mut loc_conn = OmConnection::Sqlite(SqliteConnection::establish(path.to_str().unwrap())?);
     let loc_conn = &mut loc_conn;
     let loc_workspace_name = ichno::DEFAULT_WORKSPACE_NAME;
-    let loc_workspace = SqliteWorkspaces::find_by_name(loc_conn, loc_workspace_name)?.unwrap();
+    let loc_workspace = find_workspace(loc_conn, loc_workspace_name)?;
     let loc_group_name = ichno::DEFAULT_GROUP_NAME;
-    let loc_group = SqliteGroups::find_by_name(loc_conn, loc_workspace.id, loc_group_name)?.unwrap();
-
-    let loc_stats = SqliteStats::select_by_group_id(loc_conn, loc_group.id)?;
-    for loc_stat in loc_stats.iter() {
-        let path = &loc_stat.path;
-        let glb_stat = OmStats::find_by_path(ctx.connection, glb_group.id, path)?;
-        if glb_stat == None || glb_stat.as_ref().unwrap().version != loc_stat.version {
-            let loc_histories = SqliteHistories::select_by_path(loc_conn, loc_group.id, path)?;
-            for loc_history in loc_histories.iter() {
-                if let Some(glb_stat) = glb_stat.as_ref() {
-                    if loc_history.version <= glb_stat.version {
-                        continue;
-                    }
-                }
-                let glb_footprint = if let Some(loc_footprint_id) = loc_history.footprint_id {
-                    let digest = loc_history.digest.as_ref().unwrap();
-                    let glb_footprint = OmFootprints::find_by_digest(ctx.connection, digest)?;
-                    if let Some(_) = glb_footprint {
-                        glb_footprint
-                    } else {
-                        let loc_footprint = SqliteFootprints::find(loc_conn, loc_footprint_id)?;
-                        if let Some(loc_footprint) = loc_footprint {
-                            Some(create_footprint_if_needed(
-                                ctx.connection,
-                                loc_footprint.digest.as_str(),
-                                loc_footprint.size,
-                                loc_footprint.fast_digest,
-                                now,
-                            )?)
-                        } else {
-                            warn!("Footprint (id: {}) is not found in local DB", loc_footprint_id);
-                            None
-                        }
+    let loc_group = find_group(loc_conn, &loc_workspace, loc_group_name)?;
+
+    let loc_attr_group = OmGroups::find_by_name(loc_conn, loc_workspace.id, ATTR_GROUP_NAME)?;
+    let mut loc_group_ids = vec![loc_group.id];
+    if let Some(loc_attr_group) = loc_attr_group.as_ref() {
+        loc_group_ids.push(loc_attr_group.id);
+    }
+    let loc_source_id = source_id(&loc_workspace);
+    let loc_last_history_id = OmHistories::find_last(loc_conn)?.map(|h| h.id).unwrap_or(0);
+    let mut last_history_id = glb_group.pulled_history_id.unwrap_or(0);
+    if is_source_recreated(glb_group.pulled_source_id.as_deref(), &loc_source_id, last_history_id, loc_last_history_id)
+    {
+        warn!("Local DB seems to be recreated; pulling all histories: {}", &glb_group.name);
+        last_history_id = 0;
+    }
+    if last_history_id > 0 {
+        info!("resuming pull after history {}: {}", last_history_id, &glb_group.name);
+    }
+    let batch_size = req.options.batch_size.unwrap_or(PULL_BATCH_SIZE);
+    let encoder = find_content_encoder(ctx.connection, DEFAULT_LEVEL)?;
+    let mut counts = PullCounts::default();
+    loop {
+        let loc_histories =
+            OmHistories::select_by_group_ids_after(loc_conn, &loc_group_ids, last_history_id, batch_size)?;
+        let batch_last_history_id = if let Some(loc_history) = loc_histories.last() { loc_history.id } else { break };
+        let (loc_file_histories, loc_attr_histories): (Vec<_>, Vec<_>) =
+            loc_histories.iter().partition(|h| h.group_id == loc_group.id);
+        let batch_counts = ctx.connection.transaction::<_, DomainError, _>(|conn| {
+            let mut new_ctx = Context { connection: conn, timer: Box::new(Utc::now) };
+            let mut source = SqliteSource { connection: loc_conn, workspace_id: loc_workspace.id };
+            let mut batch_counts =
+                load_local_histories(&mut new_ctx, glb_workspace, glb_group, &mut source, &loc_file_histories, now)?;
+            batch_counts.attrs +=
+                load_local_attrs(&mut new_ctx, glb_workspace, &mut source, &loc_attr_histories, &encoder, now)?;
+            OmGroups::update(
+                new_ctx.connection,
+                glb_group.id,
+                &GroupUpdateForm {
+                    pulled_history_id: Some(Some(batch_last_history_id)),
+                    pulled_source_id: Some(Some(&loc_source_id)),
+                    ..Default::default()
+                },
+            )?;
+            Ok(batch_counts)
+        })?;
+        last_history_id = batch_last_history_id;
+        counts.add(&batch_counts);
+        debug!("local histories loaded: {} (until {}): {:?}", loc_histories.len(), last_history_id, &batch_counts);
+    }
+
+    ctx.connection.transaction::<_, DomainError, _>(|conn| {
+        update_meta_group_stat(conn, glb_workspace, glb_group, path, now)?;
+        Ok(())
+    })?;
+
+    Ok(counts)
+}
+
+#[derive(Debug)]
+struct HistoryState<'a> {
+    version: i32,
+    status: Status,
+    digest: Option<&'a str>,
+    mtime: Option<NaiveDateTime>,
+    updated_at: NaiveDateTime,
+}
+
+impl<'a> HistoryState<'a> {
+    fn of_stat(stat: &'a Stat) -> HistoryState<'a> {
+        HistoryState {
+            version: stat.version,
+            status: stat.status,
+            digest: stat.digest.as_deref(),
+            mtime: stat.mtime,
+            updated_at: stat.updated_at,
+        }
+    }
+
+    fn of_history(history: &'a ichno::History) -> HistoryState<'a> {
+        HistoryState {
+            version: history.version,
+            status: history.status,
+            digest: history.digest.as_deref(),
+            mtime: history.mtime,
+            updated_at: history.updated_at,
+        }
+    }
+
+    fn is_same_as(&self, other: &HistoryState) -> bool {
+        self.status == other.status && self.digest == other.digest && self.mtime == other.mtime
+    }
+
+    /// Whether this history comes after `latest`, either by version or, for a recreated local DB, by time.
+    /// Times are compared in milliseconds since the global DB may store them with less precision.
+    fn is_newer_than(&self, latest: &HistoryState) -> bool {
+        self.version > latest.version
+            || self.updated_at.and_utc().timestamp_millis() > latest.updated_at.and_utc().timestamp_millis()
+    }
+}
+
+fn load_local_histories(
+    ctx: &mut Context,
+    glb_workspace: &Workspace,
+    glb_group: &Group,
+    source: &mut dyn LocalSource,
+    loc_histories: &[&ichno::History],
+    now: NaiveDateTime,
+) -> DomainResult<PullCounts> {
+    let mut counts = PullCounts::default();
+    let paths: Vec<&str> = loc_histories.iter().map(|h| h.path.as_str()).collect::<BTreeSet<_>>().into_iter().collect();
+    if paths.is_empty() {
+        return Ok(counts);
+    }
+    let cond =
+        StatSearchCondition { group_ids: Some(vec![glb_group.id]), paths: Some(paths.clone()), ..Default::default() };
+    let glb_stats: HashMap<String, Stat> =
+        OmStats::search(ctx.connection, glb_workspace.id, &cond)?.into_iter().map(|s| (s.path.clone(), s)).collect();
+
+    // Versions restart from 1 when the local DB is recreated, so a history is also new if it was updated after the latest
+    // one of its path and changes its state. New ones are numbered after the latest global version.
+    let mut latest_states: HashMap<&str, HistoryState> =
+        glb_stats.values().map(|s| (s.path.as_str(), HistoryState::of_stat(s))).collect();
+    let mut new_histories: Vec<(&ichno::History, i32)> = vec![];
+    for loc_history in loc_histories.iter() {
+        let state = HistoryState::of_history(loc_history);
+        let version = match latest_states.get(loc_history.path.as_str()) {
+            Some(latest) if latest.is_same_as(&state) || !state.is_newer_than(latest) => continue,
+            Some(latest) => loc_history.version.max(latest.version + 1),
+            None => loc_history.version,
+        };
+        latest_states.insert(&loc_history.path, HistoryState { version, ..state });
+        new_histories.push((loc_history, version));
+    }
+    if new_histories.is_empty() {
+        return Ok(counts);
+    }
+    let loc_histories: Vec<&ichno::History> = new_histories.iter().map(|(h, _)| *h).collect();
+    let latest_versions: HashMap<&str, i32> =
+        new_histories.iter().map(|(h, version)| (h.path.as_str(), *version)).collect();
+    let paths: Vec<&str> = latest_versions.keys().copied().collect();
+
+    let digests: Vec<&str> = loc_histories.iter().filter_map(|h| h.digest.as_deref()).collect();
+    let mut glb_footprints: HashMap<String, Footprint> =
+        OmFootprints::select_by_digests(ctx.connection, &digests)?.into_iter().map(|f| (f.digest.clone(), f)).collect();
+    let loc_footprint_ids: Vec<i32> = loc_histories
+        .iter()
+        .filter(|h| h.digest.as_ref().is_some_and(|d| !glb_footprints.contains_key(d)))
+        .filter_map(|h| h.footprint_id)
+        .collect();
+    if !loc_footprint_ids.is_empty() {
+        let loc_footprints: HashMap<String, ichno::Footprint> =
+            source.select_footprints(&loc_footprint_ids)?.into_iter().map(|f| (f.digest.clone(), f)).collect();
+        let footprint_forms: Vec<FootprintInsertForm> = loc_footprints
+            .values()
+            .map(|f| FootprintInsertForm {
+                digest: &f.digest,
+                size: f.size,
+                fast_digest: f.fast_digest,
+                created_at: now,
+            })
+            .collect();
+        counts.footprints += OmFootprints::insert_all(ctx.connection, &footprint_forms)?;
+        let digests: Vec<&str> = loc_footprints.keys().map(|d| d.as_str()).collect();
+        glb_footprints.extend(
+            OmFootprints::select_by_digests(ctx.connection, &digests)?.into_iter().map(|f| (f.digest.clone(), f)),
+        );
+    }
+
+    let history_forms: Vec<HistoryInsertForm> = new_histories
+        .iter()
+        .map(|&(loc_history, version)| {
+            let glb_footprint = loc_history.digest.as_ref().and_then(|d| glb_footprints.get(d));
+            if let (Some(loc_footprint_id), None) = (loc_history.footprint_id, glb_footprint) {
+                warn!("Footprint (id: {}) is not found in local DB", loc_footprint_id);
+            }
+            HistoryInsertForm {
+                workspace_id: glb_workspace.id,
+                group_id: glb_group.id,
+                path: &loc_history.path,
+                version,
+                status: loc_history.status,
+                mtime: loc_history.mtime,
+                footprint_id: glb_footprint.map(|o| o.id),
+                digest: glb_footprint.map(|o| o.digest.as_str()),
+                created_at: loc_history.created_at,
+                updated_at: loc_history.updated_at,
+            }
+        })
+        .collect();
+    counts.histories += OmHistories::insert_all(ctx.connection, &history_forms)?;
+
+    let glb_histories = OmHistories::select_by_paths(ctx.connection, glb_group.id, &paths)?;
+    let mut stat_forms = vec![];
+    for glb_history in glb_histories.iter() {
+        if latest_versions.get(glb_history.path.as_str()) != Some(&glb_history.version) {
+            continue;
+        }
+        let glb_footprint = glb_history.digest.as_ref().and_then(|d| glb_footprints.get(d));
+        let insert_form = StatInsertForm {
+            workspace_id: glb_workspace.id,
+            group_id: glb_group.id,
+            path: &glb_history.path,
+            history_id: glb_history.id,
+            version: glb_history.version,
+            status: glb_history.status,
+            mtime: glb_history.mtime,
+            footprint_id: glb_history.footprint_id,
+            digest: glb_footprint.map(|o| o.digest.as_str()),
+            size: glb_footprint.map(|o| o.size),
+            fast_digest: glb_footprint.map(|o| o.fast_digest),
+            created_at: glb_history.created_at,
+            updated_at: glb_history.updated_at,
+        };
+        if let Some(glb_stat) = glb_stats.get(&glb_history.path) {
+            OmStats::update(ctx.connection, glb_stat.id, &StatUpdateForm::from(insert_form))?;
+            counts.stats += 1;
+        } else {
+            stat_forms.push(insert_form);
+        }
+    }
+    counts.stats += OmStats::insert_all(ctx.connection, &stat_forms)?;
+    Ok(counts)
+}
+
+fn load_local_attrs(
+    ctx: &mut Context,
+    glb_workspace: &Workspace,
+    source: &mut dyn LocalSource,
+    loc_attr_histories: &[&ichno::History],
+    encoder: &ContentEncoder,
+    now: NaiveDateTime,
+) -> DomainResult<usize> {
+    let mut count = 0;
+    let loc_attr_group_id = if let Some(h) = loc_attr_histories.first() { h.group_id } else { return Ok(count) };
+    let paths: BTreeSet<&str> = loc_attr_histories.iter().map(|h| h.path.as_str()).collect();
+    for path in paths {
+        let (target_digest, key) = if let Some(pair) = path.split_once('/') { pair } else { continue };
+        let loc_target = source.find_footprint_by_digest(target_digest)?;
+        let loc_target = if let Some(loc_target) = loc_target { loc_target } else { continue };
+        let loc_attr = source.find_attr(loc_target.id, key)?;
+        let loc_attr = if let Some(loc_attr) = loc_attr { loc_attr } else { continue };
+        let glb_target =
+            create_footprint_if_needed(ctx.connection, target_digest, loc_target.size, loc_target.fast_digest, now)?;
+        let glb_attr = OmAttrs::find_by_target_footprint_id_and_key(
+            ctx.connection,
+            glb_workspace.id,
+            glb_target.id,
+            &loc_attr.key,
+        )?;
+        if glb_attr.as_ref().is_some_and(|a| a.updated_at >= loc_attr.updated_at) {
+            continue;
+        }
+        let loc_histories = source.select_attr_histories(loc_attr_group_id, path)?;
+        for loc_history in loc_histories.iter() {
+            if glb_attr.as_ref().is_some_and(|a| a.updated_at >= loc_history.created_at) {
+                continue;
+            }
+            let loc_footprint_id = match loc_history.footprint_id {
+                Some(loc_footprint_id) if loc_history.status == Status::Enabled => loc_footprint_id,
+                _ => {
+                    if disable_attr_and_stat_if_needed(
+                        ctx.connection,
+                        glb_workspace,
+                        &glb_target,
+                        &loc_attr.key,
+                        loc_history.created_at,
+                    )?
+                    .is_some()
+                    {
+                        count += 1;
                     }
-                } else {
-                    None
-                };
-                let glb_history = OmHistories::insert_and_find(
-                    ctx.connection,
-                    &HistoryInsertForm {
-                        workspace_id: glb_workspace.id,
-                        group_id: glb_group.id,
-                        path,
-                        version: loc_history.version,
-                        status: loc_history.status,
-                        mtime: loc_history.mtime,
-                        footprint_id: glb_footprint.as_ref().map(|o| o.id),
-                        digest: glb_footprint.as_ref().map(|o| o.digest.as_str()),
-                        created_at: loc_history.created_at,
-                        updated_at: loc_history.updated_at,
-                    },
-                )?;
-                if loc_history.version == loc_stat.version {
-                    let _glb_stat = if let Some(glb_stat) = glb_stat.as_ref() {
-                        OmStats::update_and_find(
-                            ctx.connection,
-                            glb_stat.id,
-                            &StatUpdateForm {
-                                history_id: Some(glb_history.id),
-                                version: Some(glb_history.version),
-                                status: Some(glb_history.status),
-                                mtime: Some(glb_history.mtime),
-                                footprint_id: Some(glb_history.footprint_id),
-                                digest: Some(glb_footprint.as_ref().map(|o| o.digest.as_str())),
-                                size: Some(glb_footprint.as_ref().map(|o| o.size)),
-                                fast_digest: Some(glb_footprint.as_ref().map(|o| o.fast_digest)),
-                                updated_at: Some(loc_stat.updated_at),
-                            },
-                        )?
-                    } else {
-                        OmStats::insert_and_find(
-                            ctx.connection,
-                            &StatInsertForm {
-                                workspace_id: glb_workspace.id,
-                                group_id: glb_group.id,
-                                path,
-                                history_id: glb_history.id,
-                                version: glb_history.version,
-                                status: glb_history.status,
-                                mtime: glb_history.mtime,
-                                footprint_id: glb_history.footprint_id,
-                                digest: glb_footprint.as_ref().map(|o| o.digest.as_str()),
-                                size: glb_footprint.as_ref().map(|o| o.size),
-                                fast_digest: glb_footprint.as_ref().map(|o| o.fast_digest),
-                                created_at: loc_stat.created_at,
-                                updated_at: loc_stat.updated_at,
-                            },
-                        )?
-                    };
+                    continue;
                 }
+            };
+            let body = if let Some(body) = source.load_content_body(loc_footprint_id)? {
+                body
+            } else {
+                warn!("Attr value is not found in local DB: {} (version {})", &path, loc_history.version);
+                continue;
+            };
+            let mut slice = body.as_slice();
+            if loc_history.digest.as_ref() != Some(&calc_digest(&mut slice)?) {
+                warn!("Attr value digest mismatch: {} (version {})", &path, loc_history.version);
+                continue;
             }
+            let value_summary = if loc_history.digest.as_ref() == Some(&loc_attr.value_digest) {
+                loc_attr.value_summary.as_deref()
+            } else {
+                None
+            };
+            let attr_value = AttrValue {
+                key: &loc_attr.key,
+                value: &body,
+                value_content_type: loc_attr.value_content_type,
+                value_summary,
+            };
+            create_attr_and_stat_with_bytes_if_needed(
+                ctx.connection,
+                glb_workspace,
+                &glb_target,
+                &attr_value,
+                encoder,
+                loc_history.created_at,
+            )?;
+            count += 1;
         }
     }
-
-    let _group = update_meta_group_stat(ctx.connection, glb_workspace, glb_group, path, now)?;
-
-    Ok(())
+    Ok(count)
 }
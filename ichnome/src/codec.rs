@@ -0,0 +1 @@
+pub use ichno::codec::{decode, ContentEncoder, DEFAULT_LEVEL};
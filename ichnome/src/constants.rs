@@ -1,3 +1,4 @@
 pub use ichno::{
-    ContentType, GroupType, Status, ATTR_GROUP_NAME, DEFAULT_GROUP_NAME, DEFAULT_WORKSPACE_NAME, META_GROUP_NAME,
+    ContentCodec, ContentType, GroupType, Status, ATTR_GROUP_NAME, DEFAULT_GROUP_NAME, DEFAULT_WORKSPACE_NAME,
+    META_GROUP_NAME,
 };
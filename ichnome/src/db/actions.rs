@@ -1 +0,0 @@
-include!("../../../ichno/src/db/actions.rs");
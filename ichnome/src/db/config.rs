@@ -1,13 +1,54 @@
-#[cfg(feature = "postgres")]
-use diesel::{pg::Pg, PgConnection};
-#[cfg(feature = "postgres")]
-pub type Connection = PgConnection;
-#[cfg(feature = "postgres")]
-pub type Backend = Pg;
-
-#[cfg(feature = "mysql")]
-use diesel::{mysql::Mysql, MysqlConnection};
-#[cfg(feature = "mysql")]
-pub type Connection = MysqlConnection;
-#[cfg(feature = "mysql")]
-pub type Backend = Mysql;
+use diesel::r2d2;
+
+#[cfg(not(feature = "sqlite"))]
+use crate::error::DomainError;
+use crate::{db::Connection, error::DomainResult};
+
+/// The global DB can be a SQLite file only with the `sqlite` feature.
+fn check_database_url(_database_url: &str) -> DomainResult<()> {
+    #[cfg(not(feature = "sqlite"))]
+    if !_database_url.contains("://") || _database_url.starts_with("sqlite://") {
+        return Err(DomainError::unsupported_scheme(
+            "database_url",
+            "SQLite is not supported without the sqlite feature".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+pub fn establish(database_url: &str) -> DomainResult<Connection> {
+    check_database_url(database_url)?;
+    ichno::db::establish(database_url)
+}
+
+#[derive(Debug, Clone)]
+pub struct ConnectionManager {
+    database_url: String,
+    inner: ichno::db::ConnectionManager,
+}
+
+impl ConnectionManager {
+    pub fn new(database_url: impl Into<String>) -> ConnectionManager {
+        let database_url = database_url.into();
+        ConnectionManager { inner: ichno::db::ConnectionManager::new(database_url.clone()), database_url }
+    }
+}
+
+impl r2d2::ManageConnection for ConnectionManager {
+    type Connection = Connection;
+    type Error = r2d2::Error;
+
+    fn connect(&self) -> Result<Connection, r2d2::Error> {
+        check_database_url(&self.database_url)
+            .map_err(|e| r2d2::Error::ConnectionError(diesel::ConnectionError::BadConnection(e.to_string())))?;
+        self.inner.connect()
+    }
+
+    fn is_valid(&self, conn: &mut Connection) -> Result<(), r2d2::Error> {
+        self.inner.is_valid(conn)
+    }
+
+    fn has_broken(&self, conn: &mut Connection) -> bool {
+        self.inner.has_broken(conn)
+    }
+}
@@ -1,15 +1,38 @@
-use std::error::Error;
-
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 
-use crate::db::config::Connection;
+use crate::{
+    db::Connection,
+    error::{DomainError, DomainResult},
+};
 
 #[cfg(feature = "postgres")]
-pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations-postgres");
+pub const POSTGRES_MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations-postgres");
 #[cfg(feature = "mysql")]
-pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations-mysql");
+pub const MYSQL_MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations-mysql");
+/// Server-only tables on top of the ichno schema, numbered from 1001 to stay clear of its versions.
+#[cfg(feature = "sqlite")]
+pub const SQLITE_MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations-sqlite");
 
-pub fn migrate(conn: &mut Connection) -> Result<(), Box<dyn Error>> {
-    conn.run_pending_migrations(MIGRATIONS).unwrap();
-    Ok(())
+pub fn migrate(conn: &mut Connection) -> DomainResult<()> {
+    match conn {
+        #[cfg(feature = "postgres")]
+        Connection::Pg(conn) => {
+            conn.run_pending_migrations(POSTGRES_MIGRATIONS)?;
+            Ok(())
+        }
+        #[cfg(feature = "mysql")]
+        Connection::Mysql(conn) => {
+            conn.run_pending_migrations(MYSQL_MIGRATIONS)?;
+            Ok(())
+        }
+        #[cfg(feature = "sqlite")]
+        Connection::Sqlite(conn) => {
+            conn.run_pending_migrations(ichno::db::MIGRATIONS)?;
+            conn.run_pending_migrations(SQLITE_MIGRATIONS)?;
+            Ok(())
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(DomainError::params("connection", "unsupported database backend".to_owned())),
+    }
 }
@@ -1,16 +1,11 @@
-pub(crate) mod schema;
-
 mod config;
-mod util;
-#[macro_use]
-mod macros;
 mod migrate;
 
-pub mod actions;
-
-pub use config::{Backend, Connection};
-pub use migrate::migrate;
-pub use util::{
-    Attrs as OmAttrs, Contents as OmContents, Footprints as OmFootprints, Groups as OmGroups, Histories as OmHistories,
-    StatOrder, StatSearchCondition, Stats as OmStats, Workspaces as OmWorkspaces,
+pub use config::{establish, ConnectionManager};
+pub use ichno::db::{
+    actions, tokenize_path, AttrCursor, Attrs as OmAttrs, Backend, Connection, Contents as OmContents,
+    Footprints as OmFootprints, Groups as OmGroups, Histories as OmHistories, HistoryCursor, HistorySearchCondition,
+    SnapshotCondition, SnapshotCursor, StatCursor, StatOrder, StatSearchCondition, Stats as OmStats,
+    Workspaces as OmWorkspaces,
 };
+pub use migrate::migrate;
@@ -1 +0,0 @@
-include!("../../../ichno/src/db/util.rs");
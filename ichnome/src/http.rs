@@ -0,0 +1,146 @@
+use std::{io, time::Duration};
+
+use tempfile::NamedTempFile;
+use ureq::{Agent, AgentBuilder, Request};
+use url::Url;
+
+use crate::error::{DomainError, DomainResult};
+
+const CONNECT_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Default, Debug, Clone)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum Download {
+    NotModified,
+    Modified { tempfile: NamedTempFile, validators: Validators },
+}
+
+pub(crate) fn new_agent(timeout: Option<Duration>) -> Agent {
+    let builder = AgentBuilder::new().timeout_connect(timeout.unwrap_or(Duration::from_secs(CONNECT_TIMEOUT_SECS)));
+    match timeout {
+        Some(timeout) => builder.timeout_read(timeout).build(),
+        None => builder.build(),
+    }
+}
+
+pub(crate) fn send(req: Request, validators: &Validators) -> DomainResult<Download> {
+    let mut req = req;
+    if let Some(etag) = validators.etag.as_ref() {
+        req = req.set("If-None-Match", etag);
+    }
+    if let Some(last_modified) = validators.last_modified.as_ref() {
+        req = req.set("If-Modified-Since", last_modified);
+    }
+    let url = req.url().to_owned();
+    let resp = match req.call() {
+        Ok(resp) => resp,
+        Err(ureq::Error::Status(status, resp)) => {
+            let body = resp.into_string().unwrap_or_default();
+            debug!("response body: {}", &body);
+            return Err(DomainError::internal("http", format!("{}: {}", url, status)));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if resp.status() == 304 {
+        debug!("not modified: {}", &url);
+        return Ok(Download::NotModified);
+    }
+    let validators = Validators {
+        etag: resp.header("ETag").map(|s| s.to_owned()),
+        last_modified: resp.header("Last-Modified").map(|s| s.to_owned()),
+    };
+    debug!("remote file validators: {:?}", &validators);
+    let tempfile = NamedTempFile::new()?;
+    let mut f = tempfile.reopen()?;
+    let size = io::copy(&mut resp.into_reader(), &mut f)?;
+    debug!("remote file size: {}", size);
+    Ok(Download::Modified { tempfile, validators })
+}
+
+pub fn download(url: &Url, validators: &Validators, timeout: Option<Duration>) -> DomainResult<Download> {
+    send(new_agent(timeout).request_url("GET", url), validators)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread::{self, JoinHandle},
+    };
+
+    use super::*;
+
+    /// Serves a single canned response and returns the request head received.
+    fn serve_once(response: &'static str) -> (Url, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/ichno.db", listener.local_addr().unwrap())).unwrap();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+            head
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_download_modified() {
+        let (url, handle) = serve_once(
+            "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nLast-Modified: Wed, 21 Oct 2015 07:28:00 GMT\r\n\
+             Content-Length: 5\r\nConnection: close\r\n\r\nhello",
+        );
+        let download = download(&url, &Validators::default(), Some(Duration::from_secs(10))).unwrap();
+        let head = handle.join().unwrap().to_lowercase();
+        assert!(head.starts_with("get /ichno.db "));
+        assert!(!head.contains("if-none-match"));
+        assert!(!head.contains("if-modified-since"));
+        match download {
+            Download::Modified { tempfile, validators } => {
+                assert_eq!("hello", fs::read_to_string(tempfile.path()).unwrap());
+                assert_eq!(Some("\"v1\""), validators.etag.as_deref());
+                assert_eq!(Some("Wed, 21 Oct 2015 07:28:00 GMT"), validators.last_modified.as_deref());
+            }
+            Download::NotModified => panic!("unexpected 304"),
+        }
+    }
+
+    #[test]
+    fn test_download_not_modified() {
+        let (url, handle) = serve_once("HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n");
+        let validators = Validators {
+            etag: Some("\"v1\"".to_owned()),
+            last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_owned()),
+        };
+        let download = download(&url, &validators, Some(Duration::from_secs(10))).unwrap();
+        let head = handle.join().unwrap().to_lowercase();
+        assert!(head.contains("if-none-match: \"v1\"\r\n"));
+        assert!(head.contains("if-modified-since: wed, 21 oct 2015 07:28:00 gmt\r\n"));
+        assert!(matches!(download, Download::NotModified));
+    }
+
+    #[test]
+    fn test_download_error_status() {
+        let (url, handle) =
+            serve_once("HTTP/1.1 404 Not Found\r\nContent-Length: 9\r\nConnection: close\r\n\r\nnot found");
+        let e = download(&url, &Validators::default(), Some(Duration::from_secs(10))).unwrap_err();
+        handle.join().unwrap();
+        assert_eq!("http", e.code());
+        assert!(e.to_string().contains("404"));
+    }
+}
@@ -1,10 +1,6 @@
-#[macro_use]
-extern crate diesel;
 extern crate diesel_migrations;
 #[macro_use]
 extern crate log;
-#[macro_use]
-extern crate optional_derive;
 
 pub mod action;
 pub mod db;
@@ -12,12 +8,21 @@ pub mod error;
 
 pub(crate) mod models;
 
+mod codec;
 mod constants;
+mod http;
+mod s3;
+mod source;
 mod ssh;
 
-pub use constants::{GroupType, Status, ATTR_GROUP_NAME, DEFAULT_GROUP_NAME, DEFAULT_WORKSPACE_NAME, META_GROUP_NAME};
+pub use constants::{
+    ContentCodec, ContentType, GroupType, Status, ATTR_GROUP_NAME, DEFAULT_GROUP_NAME, DEFAULT_WORKSPACE_NAME,
+    META_GROUP_NAME,
+};
+pub use ichno::push::{Changeset, PushCursor, PushResult};
 pub use models::{
-    Attr, AttrInsertForm, AttrUpdateForm, Content, ContentInsertForm, Footprint, FootprintInsertForm, Group,
-    GroupInsertForm, GroupUpdateForm, History, HistoryInsertForm, Stat, StatInsertForm, StatUpdateForm, Workspace,
-    WorkspaceInsertForm, WorkspaceUpdateForm,
+    Attr, AttrInsertForm, AttrUpdateForm, Content, ContentInsertForm, ContentUpdateForm, Footprint,
+    FootprintInsertForm, Group, GroupInsertForm, GroupUpdateForm, History, HistoryInsertForm, Stat, StatInsertForm,
+    StatUpdateForm, Workspace, WorkspaceInsertForm, WorkspaceUpdateForm,
 };
+pub use ssh::{HostKeyCheck, SshOptions};
@@ -1 +1,5 @@
-include!("../../ichno/src/models.rs");
+pub use ichno::{
+    Attr, AttrInsertForm, AttrUpdateForm, Content, ContentInsertForm, ContentUpdateForm, Footprint,
+    FootprintInsertForm, Group, GroupInsertForm, GroupUpdateForm, History, HistoryInsertForm, Stat, StatInsertForm,
+    StatUpdateForm, Workspace, WorkspaceInsertForm, WorkspaceUpdateForm,
+};
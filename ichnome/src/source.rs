@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use crate::{
+    db::{Connection, OmAttrs, OmContents, OmFootprints, OmHistories},
+    error::DomainResult,
+};
+
+pub(crate) trait LocalSource {
+    fn select_footprints(&mut self, ids: &[i32]) -> DomainResult<Vec<ichno::Footprint>>;
+    fn find_footprint_by_digest(&mut self, digest: &str) -> DomainResult<Option<ichno::Footprint>>;
+    fn find_attr(&mut self, target_footprint_id: i32, key: &str) -> DomainResult<Option<ichno::Attr>>;
+    fn select_attr_histories(&mut self, group_id: i32, path: &str) -> DomainResult<Vec<ichno::History>>;
+    fn load_content_body(&mut self, footprint_id: i32) -> DomainResult<Option<Vec<u8>>>;
+}
+
+pub(crate) struct SqliteSource<'c> {
+    pub connection: &'c mut Connection,
+    pub workspace_id: i32,
+}
+
+impl LocalSource for SqliteSource<'_> {
+    fn select_footprints(&mut self, ids: &[i32]) -> DomainResult<Vec<ichno::Footprint>> {
+        OmFootprints::select(self.connection, &ids.to_vec())
+    }
+
+    fn find_footprint_by_digest(&mut self, digest: &str) -> DomainResult<Option<ichno::Footprint>> {
+        OmFootprints::find_by_digest(self.connection, digest)
+    }
+
+    fn find_attr(&mut self, target_footprint_id: i32, key: &str) -> DomainResult<Option<ichno::Attr>> {
+        OmAttrs::find_by_target_footprint_id_and_key(self.connection, self.workspace_id, target_footprint_id, key)
+    }
+
+    fn select_attr_histories(&mut self, group_id: i32, path: &str) -> DomainResult<Vec<ichno::History>> {
+        OmHistories::select_by_path(self.connection, group_id, path)
+    }
+
+    fn load_content_body(&mut self, footprint_id: i32) -> DomainResult<Option<Vec<u8>>> {
+        let content = OmContents::find_by_footprint_id(self.connection, footprint_id)?;
+        let content = if let Some(content) = content { content } else { return Ok(None) };
+        if let Some(location) = content.location.as_ref() {
+            return Ok(Some(ichno::store::load(location)?));
+        }
+        let dictionary = if let Some(dictionary_id) = content.dictionary_id {
+            OmContents::find(self.connection, dictionary_id)?.map(|c| c.body)
+        } else {
+            None
+        };
+        Ok(Some(ichno::codec::decode(content.codec, &content.body, dictionary.as_deref())?))
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct ChangesetSource<'a> {
+    pub footprints: &'a [ichno::Footprint],
+    pub attrs: &'a [ichno::Attr],
+    pub histories: &'a [ichno::History],
+    pub contents: HashMap<i32, Vec<u8>>,
+}
+
+impl LocalSource for ChangesetSource<'_> {
+    fn select_footprints(&mut self, ids: &[i32]) -> DomainResult<Vec<ichno::Footprint>> {
+        Ok(self.footprints.iter().filter(|f| ids.contains(&f.id)).cloned().collect())
+    }
+
+    fn find_footprint_by_digest(&mut self, digest: &str) -> DomainResult<Option<ichno::Footprint>> {
+        Ok(self.footprints.iter().find(|f| f.digest == digest).cloned())
+    }
+
+    fn find_attr(&mut self, target_footprint_id: i32, key: &str) -> DomainResult<Option<ichno::Attr>> {
+        Ok(self.attrs.iter().find(|a| a.target_footprint_id == target_footprint_id && a.key == key).cloned())
+    }
+
+    fn select_attr_histories(&mut self, group_id: i32, path: &str) -> DomainResult<Vec<ichno::History>> {
+        Ok(self.histories.iter().filter(|h| h.group_id == group_id && h.path == path).cloned().collect())
+    }
+
+    fn load_content_body(&mut self, footprint_id: i32) -> DomainResult<Option<Vec<u8>>> {
+        Ok(self.contents.get(&footprint_id).cloned())
+    }
+}
@@ -1,36 +1,369 @@
 use std::{
-    error::Error,
-    io::{Read, Write},
-    net::TcpStream,
-    path::Path,
+    env,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
 };
 
-use ssh2::Session;
+use percent_encoding::percent_decode_str;
+use ssh2::{CheckResult, KnownHostFileKind, Session};
 use tempfile::NamedTempFile;
 use url::Url;
 
-pub fn download(url: &Url) -> Result<NamedTempFile, Box<dyn Error>> {
-    let host = url.host_str().unwrap();
-    let port = url.port().unwrap_or(22);
-    let username = url.username();
-    let path = Path::new(url.path());
-    let tcp = TcpStream::connect(&format!("{}:{}", host, port))?;
-    let mut sess = Session::new().unwrap();
+use crate::{
+    error::{DomainError, DomainResult},
+    http::{Download, Validators},
+};
+
+const DEFAULT_PORT: u16 = 22;
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_IDENTITY_FILES: &[&str] = &["id_ed25519", "id_ecdsa", "id_rsa"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HostKeyCheck {
+    #[default]
+    Strict,
+    AcceptNew,
+    No,
+}
+
+impl FromStr for HostKeyCheck {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "yes" | "strict" => Ok(HostKeyCheck::Strict),
+            "accept-new" => Ok(HostKeyCheck::AcceptNew),
+            "no" | "off" => Ok(HostKeyCheck::No),
+            _ => Err(DomainError::params("host_key_check", format!("invalid host key check: {}", s))),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct SshOptions {
+    pub config: Option<PathBuf>,
+    pub identity_file: Option<PathBuf>,
+    pub known_hosts: Option<PathBuf>,
+    pub host_key_check: Option<HostKeyCheck>,
+}
+
+#[derive(Default, Debug)]
+struct HostConfig {
+    host_name: Option<String>,
+    user: Option<String>,
+    port: Option<u16>,
+    identity_files: Vec<PathBuf>,
+    known_hosts: Option<PathBuf>,
+    host_key_check: Option<HostKeyCheck>,
+    connect_timeout: Option<Duration>,
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from)
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn matches_pattern(pattern: &[u8], s: &[u8]) -> bool {
+    match (pattern.first(), s.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => matches_pattern(&pattern[1..], s) || (!s.is_empty() && matches_pattern(pattern, &s[1..])),
+        (Some(b'?'), Some(_)) => matches_pattern(&pattern[1..], &s[1..]),
+        (Some(p), Some(c)) => p.eq_ignore_ascii_case(c) && matches_pattern(&pattern[1..], &s[1..]),
+        _ => false,
+    }
+}
+
+fn matches_host(patterns: &str, host: &str) -> bool {
+    let mut matched = false;
+    for pattern in patterns.split(|c: char| c.is_whitespace() || c == ',').filter(|p| !p.is_empty()) {
+        if let Some(pattern) = pattern.strip_prefix('!') {
+            if matches_pattern(pattern.as_bytes(), host.as_bytes()) {
+                return false;
+            }
+        } else if matches_pattern(pattern.as_bytes(), host.as_bytes()) {
+            matched = true;
+        }
+    }
+    matched
+}
+
+fn invalid_config(key: &str, value: &str) -> DomainError {
+    DomainError::params("ssh_config", format!("invalid {}: {}", key, value))
+}
+
+fn load_host_config(path: &Path, host: &str) -> DomainResult<HostConfig> {
+    let mut config = HostConfig::default();
+    if !path.exists() {
+        return Ok(config);
+    }
+    let mut active = true;
+    for line in fs::read_to_string(path)?.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line.split_once(|c: char| c.is_whitespace() || c == '=').unwrap_or((line, ""));
+        let value = value.trim_start_matches(|c: char| c.is_whitespace() || c == '=').trim();
+        let value = value.trim_matches('"');
+        match key.to_lowercase().as_str() {
+            "host" => active = matches_host(value, host),
+            "match" => active = false,
+            _ if !active => {}
+            "hostname" if config.host_name.is_none() => config.host_name = Some(value.replace("%h", host)),
+            "user" if config.user.is_none() => config.user = Some(value.to_owned()),
+            "port" if config.port.is_none() => {
+                config.port = Some(value.parse().map_err(|_| invalid_config(key, value))?)
+            }
+            "identityfile" => config.identity_files.push(expand_home(value)),
+            "userknownhostsfile" if config.known_hosts.is_none() => {
+                config.known_hosts = value.split_whitespace().next().map(expand_home)
+            }
+            "stricthostkeychecking" if config.host_key_check.is_none() => {
+                config.host_key_check = Some(value.parse().map_err(|_| invalid_config(key, value))?)
+            }
+            "connecttimeout" if config.connect_timeout.is_none() => {
+                config.connect_timeout =
+                    Some(Duration::from_secs(value.parse().map_err(|_| invalid_config(key, value))?))
+            }
+            _ => {}
+        }
+    }
+    trace!("ssh config for {}: {:?}", host, &config);
+    Ok(config)
+}
+
+fn connect(host: &str, port: u16, timeout: Duration) -> DomainResult<TcpStream> {
+    let mut last_error = None;
+    for addr in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(tcp) => return Ok(tcp),
+            Err(e) => {
+                debug!("failed to connect: {}: {}", addr, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| io::Error::new(io::ErrorKind::NotFound, host.to_owned())).into())
+}
+
+fn known_hosts_entry(host: &str, port: u16) -> String {
+    if port == DEFAULT_PORT {
+        host.to_owned()
+    } else {
+        format!("[{}]:{}", host, port)
+    }
+}
+
+fn verify_host_key(
+    sess: &Session,
+    host: &str,
+    port: u16,
+    known_hosts_path: &Path,
+    check: HostKeyCheck,
+) -> DomainResult<()> {
+    if check == HostKeyCheck::No {
+        warn!("host key verification is disabled: {}", host);
+        return Ok(());
+    }
+    let (key, key_type) =
+        sess.host_key().ok_or_else(|| DomainError::internal("host_key", format!("host key is missing: {}", host)))?;
+    let mut known_hosts = sess.known_hosts().map_err(DomainError::other)?;
+    if known_hosts_path.exists() {
+        known_hosts.read_file(known_hosts_path, KnownHostFileKind::OpenSSH).map_err(DomainError::other)?;
+    }
+    match known_hosts.check_port(host, port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::NotFound if check == HostKeyCheck::AcceptNew => {
+            let mut new_known_hosts = sess.known_hosts().map_err(DomainError::other)?;
+            new_known_hosts
+                .add(&known_hosts_entry(host, port), key, "", key_type.into())
+                .map_err(DomainError::other)?;
+            let tempfile = NamedTempFile::new()?;
+            new_known_hosts.write_file(tempfile.path(), KnownHostFileKind::OpenSSH).map_err(DomainError::other)?;
+            if let Some(parent) = known_hosts_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut f = OpenOptions::new().create(true).append(true).open(known_hosts_path)?;
+            f.write_all(&fs::read(tempfile.path())?)?;
+            info!("host key added: {}: {:?}", known_hosts_entry(host, port), known_hosts_path);
+            Ok(())
+        }
+        CheckResult::NotFound => Err(DomainError::params(
+            "host_key",
+            format!("host key is not found in {:?}: {}", known_hosts_path, known_hosts_entry(host, port)),
+        )),
+        CheckResult::Mismatch => Err(DomainError::params(
+            "host_key",
+            format!("host key mismatch in {:?}: {}", known_hosts_path, known_hosts_entry(host, port)),
+        )),
+        CheckResult::Failure => Err(DomainError::internal("host_key", format!("failed to check host key: {}", host))),
+    }
+}
+
+fn authenticate(sess: &Session, username: &str, identity_files: &[PathBuf]) -> DomainResult<()> {
+    for identity_file in identity_files.iter() {
+        match sess.userauth_pubkey_file(username, None, identity_file, None) {
+            Ok(()) => return Ok(()),
+            Err(e) => debug!("failed to authenticate with {:?}: {}", identity_file, e),
+        }
+    }
+    match sess.userauth_agent(username) {
+        Ok(()) => return Ok(()),
+        Err(e) => debug!("failed to authenticate with agent: {}", e),
+    }
+    if identity_files.is_empty() {
+        if let Some(ssh_dir) = home_dir().map(|h| h.join(".ssh")) {
+            for name in DEFAULT_IDENTITY_FILES.iter() {
+                let identity_file = ssh_dir.join(name);
+                if identity_file.exists() && sess.userauth_pubkey_file(username, None, &identity_file, None).is_ok() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+    Err(DomainError::params("ssh", format!("authentication failed: {}", username)))
+}
+
+pub fn download(
+    url: &Url,
+    validators: &Validators,
+    options: &SshOptions,
+    timeout: Option<Duration>,
+) -> DomainResult<Download> {
+    let alias = url.host_str().ok_or_else(|| DomainError::params("url", format!("host is missing: {}", url)))?;
+    let config_path = options.config.clone().or_else(|| home_dir().map(|h| h.join(".ssh").join("config")));
+    let config = match config_path {
+        Some(config_path) => load_host_config(&config_path, alias)?,
+        None => HostConfig::default(),
+    };
+    let host = config.host_name.as_deref().unwrap_or(alias);
+    let port = url.port().or(config.port).unwrap_or(DEFAULT_PORT);
+    let username = if !url.username().is_empty() {
+        url.username().to_owned()
+    } else if let Some(user) = config.user.as_ref() {
+        user.clone()
+    } else {
+        env::var("USER").map_err(|_| DomainError::params("user", "user is not specified".to_owned()))?
+    };
+    let timeout = timeout.or(config.connect_timeout).unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+    let known_hosts_path = options
+        .known_hosts
+        .clone()
+        .or(config.known_hosts)
+        .or_else(|| home_dir().map(|h| h.join(".ssh").join("known_hosts")))
+        .ok_or_else(|| DomainError::params("known_hosts", "known_hosts is not found".to_owned()))?;
+    let host_key_check = options.host_key_check.or(config.host_key_check).unwrap_or_default();
+    let identity_files: Vec<PathBuf> = options.identity_file.iter().cloned().chain(config.identity_files).collect();
+    debug!("connecting: {}@{}:{}", &username, host, port);
+
+    let tcp = connect(host, port, timeout)?;
+    let mut sess = Session::new().map_err(DomainError::other)?;
     sess.set_tcp_stream(tcp);
-    sess.handshake()?;
-    sess.userauth_agent(username)?;
+    sess.set_timeout(timeout.as_millis() as u32);
+    sess.handshake().map_err(DomainError::other)?;
+    verify_host_key(&sess, host, port, &known_hosts_path, host_key_check)?;
+    authenticate(&sess, &username, &identity_files)?;
 
-    let (mut remote_file, stat) = sess.scp_recv(path)?;
-    debug!("remote file size: {}", stat.size());
+    let sftp = sess.sftp().map_err(DomainError::other)?;
+    let path = PathBuf::from(percent_decode_str(url.path()).decode_utf8().map_err(DomainError::other)?.into_owned());
+    let stat = sftp.stat(&path).map_err(DomainError::other)?;
+    let size = stat.size.unwrap_or(0);
+    debug!("remote file size: {}", size);
+    let etag = format!("\"{:x}-{:x}\"", stat.mtime.unwrap_or(0), size);
+    if validators.etag.as_ref() == Some(&etag) {
+        debug!("not modified: {}", url);
+        return Ok(Download::NotModified);
+    }
     let tempfile = NamedTempFile::new()?;
     let mut f = tempfile.reopen()?;
-    let mut buf = [0u8; 8192];
-    loop {
-        let n = remote_file.read(&mut buf)?;
-        if n == 0 {
-            break;
-        }
-        f.write(&buf[0..n])?;
+    let mut remote_file = sftp.open(&path).map_err(DomainError::other)?;
+    io::copy(&mut remote_file, &mut f)?;
+    Ok(Download::Modified { tempfile, validators: Validators { etag: Some(etag), last_modified: None } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern(b"*", b"example.com"));
+        assert!(matches_pattern(b"*", b""));
+        assert!(matches_pattern(b"*.example.com", b"db.example.com"));
+        assert!(!matches_pattern(b"*.example.com", b"example.com"));
+        assert!(matches_pattern(b"db?.example.com", b"db1.example.com"));
+        assert!(!matches_pattern(b"db?.example.com", b"db.example.com"));
+        assert!(!matches_pattern(b"db?.example.com", b"db12.example.com"));
+        assert!(matches_pattern(b"DB*", b"db1"));
+        assert!(!matches_pattern(b"db", b"db1"));
+    }
+
+    #[test]
+    fn test_matches_host() {
+        assert!(matches_host("*", "db1"));
+        assert!(matches_host("web? db?", "db1"));
+        assert!(matches_host("web?,db?", "db1"));
+        assert!(!matches_host("web?", "db1"));
+        assert!(matches_host("*.example.com !bastion.example.com", "db.example.com"));
+        assert!(!matches_host("*.example.com !bastion.example.com", "bastion.example.com"));
+        assert!(!matches_host("!bastion.example.com *.example.com", "bastion.example.com"));
+        // A negated pattern alone never matches.
+        assert!(!matches_host("!bastion.example.com", "db.example.com"));
+    }
+
+    #[test]
+    fn test_load_host_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config");
+        fs::write(
+            &path,
+            "# comment\n\
+             Host db? !db9\n\
+             \x20   HostName %h.internal\n\
+             \x20   Port 2222\n\
+             \x20   IdentityFile /keys/db\n\
+             Host db1\n\
+             \x20   User ignored\n\
+             \x20   Port 22\n\
+             Match host db1\n\
+             \x20   User matched\n\
+             Host *\n\
+             \x20   User = \"alice\"\n\
+             \x20   IdentityFile /keys/default\n\
+             \x20   StrictHostKeyChecking accept-new\n\
+             \x20   ConnectTimeout 5\n",
+        )
+        .unwrap();
+
+        let config = load_host_config(&path, "db1").unwrap();
+        assert_eq!(Some("db1.internal"), config.host_name.as_deref());
+        assert_eq!(Some(2222), config.port);
+        assert_eq!(Some("ignored"), config.user.as_deref());
+        assert_eq!(vec![PathBuf::from("/keys/db"), PathBuf::from("/keys/default")], config.identity_files);
+        assert_eq!(Some(HostKeyCheck::AcceptNew), config.host_key_check);
+        assert_eq!(Some(Duration::from_secs(5)), config.connect_timeout);
+
+        let config = load_host_config(&path, "db9").unwrap();
+        assert_eq!(None, config.host_name);
+        assert_eq!(None, config.port);
+        assert_eq!(Some("alice"), config.user.as_deref());
+        assert_eq!(vec![PathBuf::from("/keys/default")], config.identity_files);
+
+        let config = load_host_config(&dir.path().join("missing"), "db1").unwrap();
+        assert_eq!(None, config.host_name);
+        assert!(config.identity_files.is_empty());
+
+        fs::write(&path, "Host *\n    Port ssh\n").unwrap();
+        assert_eq!("ssh_config", load_host_config(&path, "db1").unwrap_err().code());
     }
-    Ok(tempfile)
 }
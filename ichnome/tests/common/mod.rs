@@ -0,0 +1,103 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use ichno::{actions as loc_actions, ContentType};
+use ichnome::{
+    action::{self, Context, SetupOptions, SetupRequest},
+    db::{self, Connection, OmAttrs, OmGroups, OmHistories, OmStats, OmWorkspaces},
+    Group, History, Status, DEFAULT_WORKSPACE_NAME,
+};
+
+pub struct LocalDb {
+    pub dir: PathBuf,
+    pub conn: ichno::db::Connection,
+    group_name: String,
+}
+
+impl LocalDb {
+    pub fn create(dir: &Path, group_name: &str) -> LocalDb {
+        let mut conn = ichno::db::establish(dir.join("ichno.db").to_str().unwrap()).unwrap();
+        ichno::db::migrate(&mut conn).unwrap();
+        LocalDb { dir: dir.to_owned(), conn, group_name: group_name.to_owned() }
+    }
+
+    pub fn db_path(&self) -> PathBuf {
+        self.dir.join("ichno.db")
+    }
+
+    pub fn with_context<T>(&mut self, f: impl FnOnce(&mut loc_actions::Context) -> T) -> T {
+        let db_path = self.db_path();
+        let mut ctx = loc_actions::Context {
+            connection: &mut self.conn,
+            db_path: &db_path,
+            workspace_name: DEFAULT_WORKSPACE_NAME,
+            workspace: None,
+            group_name: &self.group_name,
+            group: None,
+            timer: Box::new(Utc::now),
+        };
+        loc_actions::pre_process(&mut ctx).unwrap();
+        f(&mut ctx)
+    }
+
+    pub fn scan(&mut self, path: &str, body: &str) {
+        fs::write(self.dir.join(path), body).unwrap();
+        self.rescan(path);
+    }
+
+    pub fn rescan(&mut self, path: &str) {
+        let file_path = self.dir.join(path);
+        self.with_context(|ctx| {
+            let now = ctx.naive_current_time();
+            let group = ctx.group.as_ref().unwrap();
+            ichno::db::actions::update_stat_with_paths_if_needed(ctx.connection, group, path, &file_path, now)
+                .unwrap()
+                .unwrap();
+        });
+    }
+
+    pub fn set_attr(&mut self, path: &str, key: &str, value: &str) {
+        self.with_context(|ctx| loc_actions::set_file_attr(ctx, path, key, value.as_bytes(), ContentType::Json))
+            .unwrap();
+    }
+}
+
+pub fn setup_global(dir: &Path) -> Connection {
+    let mut conn = db::establish(dir.join("ichnome.db").to_str().unwrap()).unwrap();
+    db::migrate(&mut conn).unwrap();
+    let mut ctx = Context { connection: &mut conn, timer: Box::new(Utc::now) };
+    let setup_req =
+        SetupRequest { workspace_name: DEFAULT_WORKSPACE_NAME.to_owned(), options: SetupOptions::default() };
+    action::setup(&mut ctx, &setup_req).unwrap();
+    conn
+}
+
+pub fn find_group(conn: &mut Connection, name: &str) -> Group {
+    let workspace = OmWorkspaces::find_by_name(conn, DEFAULT_WORKSPACE_NAME).unwrap().unwrap();
+    OmGroups::find_by_name(conn, workspace.id, name).unwrap().unwrap()
+}
+
+pub fn select_histories(conn: &mut Connection, group_name: &str) -> Vec<History> {
+    let group = find_group(conn, group_name);
+    OmHistories::select_by_group_ids_after(conn, &[group.id], 0, 1000).unwrap()
+}
+
+pub fn select_stat_paths(conn: &mut Connection, group_name: &str) -> Vec<String> {
+    let group = find_group(conn, group_name);
+    let mut paths: Vec<_> = OmStats::select_by_group_id(conn, group.id)
+        .unwrap()
+        .into_iter()
+        .filter(|s| s.status == Status::Enabled)
+        .map(|s| s.path)
+        .collect();
+    paths.sort();
+    paths
+}
+
+pub fn count_attrs(conn: &mut Connection, key: &str) -> usize {
+    let workspace = OmWorkspaces::find_by_name(conn, DEFAULT_WORKSPACE_NAME).unwrap().unwrap();
+    OmAttrs::search(conn, workspace.id, key, None, None, 100).unwrap().len()
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "sqlite")]
+
+mod common;
+
+use chrono::Utc;
+use ichno::actions::{self as loc_actions, ExportOptions};
+use ichnome::{
+    action::{self, Context, ImportRequest, ImportResponse},
+    db::Connection,
+    ATTR_GROUP_NAME, DEFAULT_WORKSPACE_NAME,
+};
+
+use crate::common::{count_attrs, find_group, select_histories, select_stat_paths, setup_global, LocalDb};
+
+const GROUP_NAME: &str = "imported";
+
+fn import(conn: &mut Connection, mut r: &[u8]) -> ImportResponse {
+    let mut ctx = Context { connection: conn, timer: Box::new(Utc::now) };
+    let req = ImportRequest { workspace_name: DEFAULT_WORKSPACE_NAME.to_owned(), group_name: GROUP_NAME.to_owned() };
+    action::import(&mut ctx, &req, &mut r).unwrap()
+}
+
+#[test]
+fn test_export_and_import() {
+    let loc_dir = tempfile::tempdir().unwrap();
+    let glb_dir = tempfile::tempdir().unwrap();
+    let mut loc = LocalDb::create(loc_dir.path(), "photos");
+    loc.scan("a.txt", "a1");
+    loc.scan("b.txt", "b");
+    loc.set_attr("a.txt", "rating", "1");
+    loc.scan("a.txt", "a2");
+    loc.set_attr("b.txt", "rating", "2");
+    loc.set_attr("b.txt", "rating", "3");
+
+    let mut exported = vec![];
+    let options = ExportOptions { since: None, batch_size: Some(2) };
+    let footer = loc.with_context(|ctx| loc_actions::export(ctx, &options, &mut exported)).unwrap();
+    assert_eq!(6, footer.histories);
+
+    let mut conn = setup_global(glb_dir.path());
+    let resp = import(&mut conn, &exported);
+    assert_eq!(Some(GROUP_NAME), resp.group.as_ref().map(|g| g.name.as_str()));
+    assert_eq!(footer.last_history_id, resp.footer.and_then(|f| f.last_history_id));
+    assert_eq!(3, resp.counts.histories);
+
+    let loc_histories = loc.with_context(|ctx| {
+        let group = ctx.group.clone().unwrap();
+        ichno::db::Histories::select_by_group_ids_after(ctx.connection, &[group.id], 0, 100).unwrap()
+    });
+    let summarize = |histories: Vec<ichno::History>| -> Vec<_> {
+        histories.into_iter().map(|h| (h.path, h.version, h.status, h.digest, h.mtime)).collect()
+    };
+    assert_eq!(summarize(loc_histories), summarize(select_histories(&mut conn, GROUP_NAME)));
+    assert_eq!(vec!["a.txt", "b.txt"], select_stat_paths(&mut conn, GROUP_NAME));
+    assert_eq!(3, select_histories(&mut conn, ATTR_GROUP_NAME).len());
+    assert_eq!(2, count_attrs(&mut conn, "rating"));
+
+    // Importing the same stream again changes nothing.
+    let resp = import(&mut conn, &exported);
+    assert_eq!(0, resp.counts.histories);
+    assert_eq!(0, resp.counts.attrs);
+    assert_eq!(3, select_histories(&mut conn, GROUP_NAME).len());
+    assert_eq!(find_group(&mut conn, GROUP_NAME).id, resp.group.unwrap().id);
+}
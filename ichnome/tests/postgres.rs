@@ -0,0 +1,50 @@
+#![cfg(feature = "postgres")]
+
+use std::env;
+
+use chrono::{NaiveDate, Utc};
+use diesel::{connection::SimpleConnection, sql_query, sql_types::BigInt, QueryableByName, RunQueryDsl};
+use ichnome::{
+    db::{self, actions, Connection, OmWorkspaces},
+    DEFAULT_WORKSPACE_NAME,
+};
+
+#[derive(QueryableByName)]
+struct Count {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+/// Runs only when `ICHNOME_TEST_POSTGRES_URL` points at a scratch database.
+#[test]
+fn test_timestamptz_round_trip() {
+    let database_url = match env::var("ICHNOME_TEST_POSTGRES_URL") {
+        Ok(url) => url,
+        Err(_) => return,
+    };
+    let mut conn = db::establish(&database_url).unwrap();
+    db::migrate(&mut conn).unwrap();
+
+    // Timestamps are mapped to `timestamptz` columns as naive UTC times.
+    let created_at = NaiveDate::from_ymd_opt(2020, 1, 2).unwrap().and_hms_micro_opt(3, 4, 5, 678_901).unwrap();
+    let name = format!("{}-{}", DEFAULT_WORKSPACE_NAME, Utc::now().timestamp_nanos_opt().unwrap());
+    let workspace = actions::create_workspace_if_needed(&mut conn, &name, created_at).unwrap();
+    assert_eq!(created_at, workspace.created_at);
+
+    // Both the stored instant and the values read back must not depend on the session time zone.
+    conn.batch_execute("SET TIME ZONE 'Asia/Tokyo'").unwrap();
+    assert_eq!(created_at, OmWorkspaces::find_by_name(&mut conn, &name).unwrap().unwrap().created_at);
+    let pg_conn = match &mut conn {
+        Connection::Pg(pg_conn) => pg_conn,
+        _ => unreachable!(),
+    };
+    let counts: Vec<Count> = sql_query(format!(
+        "SELECT COUNT(*) AS count FROM workspaces \
+         WHERE name = '{}' AND created_at = TIMESTAMPTZ '2020-01-02 03:04:05.678901+00'",
+        &name
+    ))
+    .load(pg_conn)
+    .unwrap();
+    pg_conn.batch_execute(&format!("DELETE FROM workspaces WHERE name = '{}'", &name)).unwrap();
+    assert_eq!(1, counts[0].count);
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "sqlite")]
+
+mod common;
+
+use std::fs;
+
+use chrono::Utc;
+use diesel::connection::SimpleConnection;
+use ichnome::{
+    action::{self, Context, PullOptions, PullRequest, RegisterOptions, RegisterRequest},
+    db::{Connection, OmStats},
+    ATTR_GROUP_NAME, DEFAULT_GROUP_NAME, DEFAULT_WORKSPACE_NAME,
+};
+use url::Url;
+
+use crate::common::{count_attrs, find_group, select_histories, select_stat_paths, setup_global, LocalDb};
+
+const GROUP_NAME: &str = "local";
+
+fn register(conn: &mut Connection, local_db: &LocalDb) {
+    let mut ctx = Context { connection: conn, timer: Box::new(Utc::now) };
+    let req = RegisterRequest {
+        workspace_name: DEFAULT_WORKSPACE_NAME.to_owned(),
+        group_name: GROUP_NAME.to_owned(),
+        url: Url::from_file_path(local_db.db_path()).unwrap().to_string(),
+        options: RegisterOptions::default(),
+    };
+    action::register(&mut ctx, &req).unwrap();
+}
+
+fn pull(conn: &mut Connection, batch_size: i64) -> ichnome::error::DomainResult<action::PullResponse> {
+    let mut ctx = Context { connection: conn, timer: Box::new(Utc::now) };
+    let req = PullRequest {
+        workspace_name: DEFAULT_WORKSPACE_NAME.to_owned(),
+        group_name: GROUP_NAME.to_owned(),
+        options: PullOptions { batch_size: Some(batch_size), ..Default::default() },
+    };
+    action::pull(&mut ctx, &req)
+}
+
+#[test]
+fn test_pull_resumes_after_failed_batch() {
+    let loc_dir = tempfile::tempdir().unwrap();
+    let glb_dir = tempfile::tempdir().unwrap();
+    let mut loc = LocalDb::create(loc_dir.path(), DEFAULT_GROUP_NAME);
+    loc.scan("a.txt", "a");
+    loc.scan("b.txt", "b");
+    loc.set_attr("a.txt", "rating", "1");
+    loc.scan("c.txt", "c");
+    loc.set_attr("b.txt", "note", "\"later\"");
+    loc.scan("d.txt", "d");
+
+    // Move the value of the second attr out to a store file that does not exist yet, so that the third batch fails.
+    let value_path = loc_dir.path().join("note-value");
+    loc.conn
+        .batch_execute(&format!(
+            "UPDATE contents SET location = '{}', body = X'' \
+             WHERE footprint_id = (SELECT value_footprint_id FROM attrs WHERE key = 'note')",
+            Url::from_file_path(&value_path).unwrap()
+        ))
+        .unwrap();
+
+    let mut conn = setup_global(glb_dir.path());
+    register(&mut conn, &loc);
+    assert!(pull(&mut conn, 2).is_err());
+    assert_eq!(Some(4), find_group(&mut conn, GROUP_NAME).pulled_history_id);
+    assert_eq!(vec!["a.txt", "b.txt", "c.txt"], select_stat_paths(&mut conn, GROUP_NAME));
+    assert_eq!(1, count_attrs(&mut conn, "rating"));
+    assert_eq!(0, count_attrs(&mut conn, "note"));
+
+    fs::write(&value_path, "\"later\"").unwrap();
+    let resp = pull(&mut conn, 2).unwrap();
+    assert_eq!(1, resp.counts.histories);
+    assert_eq!(1, resp.counts.attrs);
+    assert_eq!(Some(6), find_group(&mut conn, GROUP_NAME).pulled_history_id);
+    assert_eq!(vec!["a.txt", "b.txt", "c.txt", "d.txt"], select_stat_paths(&mut conn, GROUP_NAME));
+    let histories = select_histories(&mut conn, GROUP_NAME);
+    assert_eq!(4, histories.len());
+    assert!(histories.iter().all(|h| h.version == 1));
+    assert_eq!(2, select_histories(&mut conn, ATTR_GROUP_NAME).len());
+    assert_eq!(1, count_attrs(&mut conn, "rating"));
+    assert_eq!(1, count_attrs(&mut conn, "note"));
+}
+
+#[test]
+fn test_pull_after_local_db_recreated() {
+    let loc_dir = tempfile::tempdir().unwrap();
+    let glb_dir = tempfile::tempdir().unwrap();
+    let mut loc = LocalDb::create(loc_dir.path(), DEFAULT_GROUP_NAME);
+    loc.scan("a.txt", "a1");
+    loc.scan("b.txt", "b");
+    loc.scan("c.txt", "c");
+    let mut conn = setup_global(glb_dir.path());
+    register(&mut conn, &loc);
+    assert_eq!(3, pull(&mut conn, 100).unwrap().counts.histories);
+
+    // A recreated local DB restarts history IDs and versions from 1.
+    drop(loc);
+    fs::remove_file(loc_dir.path().join("ichno.db")).unwrap();
+    let mut loc = LocalDb::create(loc_dir.path(), DEFAULT_GROUP_NAME);
+    loc.scan("a.txt", "a2");
+    loc.rescan("b.txt");
+    assert_eq!(1, pull(&mut conn, 100).unwrap().counts.histories);
+
+    loc.scan("a.txt", "a3");
+    assert_eq!(1, pull(&mut conn, 100).unwrap().counts.histories);
+
+    let versions = |conn: &mut Connection, path: &str| -> Vec<i32> {
+        select_histories(conn, GROUP_NAME).into_iter().filter(|h| h.path == path).map(|h| h.version).collect()
+    };
+    assert_eq!(vec![1, 2, 3], versions(&mut conn, "a.txt"));
+    assert_eq!(vec![1], versions(&mut conn, "b.txt"));
+    assert_eq!(vec![1], versions(&mut conn, "c.txt"));
+    let group = find_group(&mut conn, GROUP_NAME);
+    let stat = OmStats::find_by_path(&mut conn, group.id, "a.txt").unwrap().unwrap();
+    assert_eq!(3, stat.version);
+    assert_eq!(ichno::db::actions::calc_digest(&mut "a3".as_bytes()).unwrap(), stat.digest.unwrap());
+}
+
+#[test]
+fn test_pull_after_local_db_recreated_with_more_histories() {
+    let loc_dir = tempfile::tempdir().unwrap();
+    let glb_dir = tempfile::tempdir().unwrap();
+    let mut loc = LocalDb::create(loc_dir.path(), DEFAULT_GROUP_NAME);
+    loc.scan("a.txt", "a1");
+    let mut conn = setup_global(glb_dir.path());
+    register(&mut conn, &loc);
+    assert_eq!(1, pull(&mut conn, 100).unwrap().counts.histories);
+
+    // The recreated local DB has already gone past the cursor, so that only its identity tells it apart.
+    drop(loc);
+    fs::remove_file(loc_dir.path().join("ichno.db")).unwrap();
+    let mut loc = LocalDb::create(loc_dir.path(), DEFAULT_GROUP_NAME);
+    loc.scan("b.txt", "b");
+    loc.scan("c.txt", "c");
+    assert_eq!(2, pull(&mut conn, 100).unwrap().counts.histories);
+    assert_eq!(vec!["a.txt", "b.txt", "c.txt"], select_stat_paths(&mut conn, GROUP_NAME));
+    assert_eq!(Some(2), find_group(&mut conn, GROUP_NAME).pulled_history_id);
+}
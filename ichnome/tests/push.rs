@@ -0,0 +1,124 @@
+#![cfg(feature = "sqlite")]
+
+#[allow(dead_code)]
+mod common;
+
+use chrono::Utc;
+use ichno::db::{actions, Footprints, Groups, Histories, Workspaces};
+use ichnome::{
+    action::{self, Context, PullRequest, PushRequest, RegisterOptions, RegisterRequest},
+    db::Connection,
+    error::{DomainError, DomainResult},
+    Changeset, ATTR_GROUP_NAME, DEFAULT_GROUP_NAME, DEFAULT_WORKSPACE_NAME, META_GROUP_NAME,
+};
+
+use crate::common::{find_group, setup_global, LocalDb};
+
+const URL: &str = "file:///home/user/ichno.db";
+const SOURCE_ID: &str = "2020-01-01T00:00:00";
+
+fn push(conn: &mut Connection, group_name: &str) -> DomainResult<action::PushResponse> {
+    let mut ctx = Context { connection: conn, timer: Box::new(Utc::now) };
+    let changeset = Changeset {
+        url: URL.to_owned(),
+        source_id: SOURCE_ID.to_owned(),
+        from_history_id: None,
+        histories: vec![],
+        footprints: vec![],
+    };
+    let req =
+        PushRequest { workspace_name: DEFAULT_WORKSPACE_NAME.to_owned(), group_name: group_name.to_owned(), changeset };
+    action::push(&mut ctx, &req)
+}
+
+#[test]
+fn test_push_rejects_pull_groups() {
+    let glb_dir = tempfile::tempdir().unwrap();
+    let mut conn = setup_global(glb_dir.path());
+    let mut ctx = Context { connection: &mut conn, timer: Box::new(Utc::now) };
+    let req = RegisterRequest {
+        workspace_name: DEFAULT_WORKSPACE_NAME.to_owned(),
+        group_name: "pulled".to_owned(),
+        url: URL.to_owned(),
+        options: RegisterOptions::default(),
+    };
+    action::register(&mut ctx, &req).unwrap();
+
+    assert!(matches!(push(&mut conn, "pulled"), Err(DomainError::Conflict(_))));
+    let mut ctx = Context { connection: &mut conn, timer: Box::new(Utc::now) };
+    let cursor = action::find_push_cursor(&mut ctx, DEFAULT_WORKSPACE_NAME, "pulled");
+    assert!(matches!(cursor, Err(DomainError::Conflict(_))));
+    assert_eq!(None, find_group(&mut conn, "pulled").pulled_history_id);
+
+    // Nor can pushed groups be pulled.
+    push(&mut conn, "pushed").unwrap();
+    let mut ctx = Context { connection: &mut conn, timer: Box::new(Utc::now) };
+    let req = PullRequest {
+        workspace_name: DEFAULT_WORKSPACE_NAME.to_owned(),
+        group_name: "pushed".to_owned(),
+        options: Default::default(),
+    };
+    assert!(matches!(action::pull(&mut ctx, &req), Err(DomainError::Conflict(_))));
+}
+
+#[test]
+fn test_push_rejects_reserved_groups() {
+    let glb_dir = tempfile::tempdir().unwrap();
+    let mut conn = setup_global(glb_dir.path());
+    for group_name in [META_GROUP_NAME, ATTR_GROUP_NAME] {
+        assert!(matches!(push(&mut conn, group_name), Err(DomainError::Params(_))));
+        let mut ctx = Context { connection: &mut conn, timer: Box::new(Utc::now) };
+        let cursor = action::find_push_cursor(&mut ctx, DEFAULT_WORKSPACE_NAME, group_name);
+        assert!(matches!(cursor, Err(DomainError::Params(_))));
+    }
+}
+
+#[test]
+fn test_push_saves_source_id() {
+    let loc_dir = tempfile::tempdir().unwrap();
+    let glb_dir = tempfile::tempdir().unwrap();
+    let mut loc = LocalDb::create(loc_dir.path(), DEFAULT_GROUP_NAME);
+    loc.scan("a.txt", "a");
+    let loc_workspace = Workspaces::find_by_name(&mut loc.conn, DEFAULT_WORKSPACE_NAME).unwrap().unwrap();
+    let loc_group = Groups::find_by_name(&mut loc.conn, loc_workspace.id, DEFAULT_GROUP_NAME).unwrap().unwrap();
+    let histories = Histories::select_by_group_ids_after(&mut loc.conn, &[loc_group.id], 0, 100).unwrap();
+    let footprint_ids: Vec<i32> = histories.iter().filter_map(|h| h.footprint_id).collect();
+    let footprints = Footprints::select(&mut loc.conn, &footprint_ids).unwrap();
+    let source_id = actions::source_id(&loc_workspace);
+
+    let mut conn = setup_global(glb_dir.path());
+    let mut ctx = Context { connection: &mut conn, timer: Box::new(Utc::now) };
+    let changeset = Changeset {
+        url: loc_group.url.clone(),
+        source_id: source_id.clone(),
+        from_history_id: None,
+        histories,
+        footprints,
+    };
+    let req =
+        PushRequest { workspace_name: DEFAULT_WORKSPACE_NAME.to_owned(), group_name: "pushed".to_owned(), changeset };
+    action::push(&mut ctx, &req).unwrap();
+    let cursor = action::find_push_cursor(&mut ctx, DEFAULT_WORKSPACE_NAME, "pushed").unwrap().unwrap();
+    assert_eq!(Some(1), cursor.pulled_history_id);
+    assert_eq!(Some(source_id), cursor.pulled_source_id);
+    assert!(!actions::is_source_recreated(
+        cursor.pulled_source_id.as_deref(),
+        &actions::source_id(&loc_workspace),
+        1,
+        1
+    ));
+
+    // A recreated local DB is detected even when it has gone past the cursor.
+    drop(loc);
+    std::fs::remove_file(loc_dir.path().join("ichno.db")).unwrap();
+    let mut loc = LocalDb::create(loc_dir.path(), DEFAULT_GROUP_NAME);
+    loc.scan("b.txt", "b");
+    loc.scan("c.txt", "c");
+    let loc_workspace = Workspaces::find_by_name(&mut loc.conn, DEFAULT_WORKSPACE_NAME).unwrap().unwrap();
+    assert!(actions::is_source_recreated(
+        cursor.pulled_source_id.as_deref(),
+        &actions::source_id(&loc_workspace),
+        1,
+        2
+    ));
+}
@@ -1,14 +1,22 @@
 #[macro_use]
 extern crate log;
 
-use std::{env, error::Error, process::exit};
+use std::{
+    env,
+    fs::File,
+    io::{stdin, BufReader},
+    path::PathBuf,
+    process::exit,
+    time::Duration,
+};
 
 use chrono::Utc;
-use diesel::Connection;
 use ichnome::{
     action,
-    action::{PullOptions, PullRequest, RegisterOptions, RegisterRequest, SetupOptions, SetupRequest},
-    db::Connection as OmConnection,
+    action::{ImportRequest, PullOptions, PullRequest, RegisterOptions, RegisterRequest, SetupOptions, SetupRequest},
+    db,
+    error::{DomainError, DomainResult},
+    HostKeyCheck, SshOptions,
 };
 use structopt::{clap, StructOpt};
 
@@ -30,6 +38,7 @@ pub enum SubCommands {
     Setup(Setup),
     Register(Register),
     Pull(Pull),
+    Import(Import),
 }
 
 #[derive(Debug, StructOpt)]
@@ -63,19 +72,52 @@ pub struct Register {
 pub struct Pull {
     #[structopt(name = "GROUP")]
     pub group_name: String,
+
+    #[structopt(long, name = "N")]
+    pub batch_size: Option<i64>,
+
+    #[structopt(short, long)]
+    pub force: bool,
+
+    #[structopt(long, name = "SECS")]
+    pub timeout: Option<u64>,
+
+    #[structopt(long, name = "SSH_CONFIG", parse(from_os_str))]
+    pub ssh_config: Option<PathBuf>,
+
+    #[structopt(short, long, name = "IDENTITY_FILE", parse(from_os_str))]
+    pub identity_file: Option<PathBuf>,
+
+    #[structopt(long, name = "KNOWN_HOSTS", parse(from_os_str))]
+    pub known_hosts: Option<PathBuf>,
+
+    #[structopt(long, name = "yes|accept-new|no")]
+    pub host_key_check: Option<HostKeyCheck>,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct Import {
+    #[structopt(name = "GROUP")]
+    pub group_name: String,
+
+    #[structopt(name = "FILE", parse(from_os_str))]
+    pub file: Option<PathBuf>,
 }
 
-fn main_with_error() -> Result<i32, Box<dyn Error>> {
+fn main_with_error() -> DomainResult<i32> {
     dotenv::dotenv().ok();
     env_logger::init();
     let database_url = env::var("DATABASE_URL").unwrap_or("ichno.db".to_owned());
-    let mut conn = OmConnection::establish(&database_url).expect(&format!("Error connecting to {}", database_url));
-    let mut ctx = action::Context { connection: &mut conn, timer: Box::new(|| Utc::now()) };
+    let mut conn = db::establish(&database_url)?;
+    let mut ctx = action::Context { connection: &mut conn, timer: Box::new(Utc::now) };
     let opt = Opt::from_args();
-    let workspace_name = opt.workspace.or_else(|| env::var("ICHNOME_WORKSPACE").ok()).unwrap();
+    let workspace_name = opt
+        .workspace
+        .or_else(|| env::var("ICHNOME_WORKSPACE").ok())
+        .ok_or_else(|| DomainError::params("workspace", "workspace is not specified".to_owned()))?;
     match opt.sub {
         SubCommands::Migrate(_) => {
-            ichnome::db::migrate(&mut ctx.connection)?;
+            ichnome::db::migrate(ctx.connection)?;
         }
         SubCommands::Setup(setup) => {
             action::setup(
@@ -98,10 +140,44 @@ fn main_with_error() -> Result<i32, Box<dyn Error>> {
             )?;
         }
         SubCommands::Pull(pull) => {
-            action::pull(
+            let resp = action::pull(
                 &mut ctx,
-                &PullRequest { workspace_name, group_name: pull.group_name, options: PullOptions {} },
+                &PullRequest {
+                    workspace_name,
+                    group_name: pull.group_name,
+                    options: PullOptions {
+                        batch_size: pull.batch_size,
+                        force: pull.force,
+                        timeout: pull.timeout.map(Duration::from_secs),
+                        ssh: SshOptions {
+                            config: pull.ssh_config,
+                            identity_file: pull.identity_file,
+                            known_hosts: pull.known_hosts,
+                            host_key_check: pull.host_key_check,
+                        },
+                    },
+                },
             )?;
+            let counts = &resp.counts;
+            println!(
+                "{}: {} histories, {} stats, {} footprints, {} attrs",
+                &resp.group.name, counts.histories, counts.stats, counts.footprints, counts.attrs
+            );
+        }
+        SubCommands::Import(import) => {
+            let req = ImportRequest { workspace_name, group_name: import.group_name };
+            let resp = match import.file.as_ref().filter(|f| f.as_os_str() != "-") {
+                Some(file) => action::import(&mut ctx, &req, &mut BufReader::new(File::open(file)?))?,
+                None => action::import(&mut ctx, &req, &mut stdin().lock())?,
+            };
+            let counts = &resp.counts;
+            println!(
+                "{}: {} histories, {} stats, {} footprints, {} attrs",
+                &req.group_name, counts.histories, counts.stats, counts.footprints, counts.attrs
+            );
+            if let Some(footer) = resp.footer.as_ref() {
+                info!("last history id: {:?}", footer.last_history_id);
+            }
         }
     }
     Ok(0)
@@ -110,6 +186,9 @@ fn main_with_error() -> Result<i32, Box<dyn Error>> {
 fn main() {
     match main_with_error() {
         Ok(code) => exit(code),
-        Err(e) => error!("{}", e),
+        Err(e) => {
+            error!("{}", e);
+            exit(e.exit_code())
+        }
     }
 }
@@ -0,0 +1,105 @@
+use std::fmt::{self, Display};
+
+use actix_web::{error::BlockingError, http::StatusCode, HttpResponse, ResponseError};
+use diesel::r2d2::PoolError;
+use ichnome::error::DomainError;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct ErrorDetailBody {
+    pub code: &'static str,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub code: &'static str,
+    pub message: String,
+    pub details: Vec<ErrorDetailBody>,
+}
+
+#[derive(Debug)]
+pub struct WebError {
+    status: StatusCode,
+    body: ErrorBody,
+}
+
+pub type WebResult<T> = Result<T, WebError>;
+
+impl WebError {
+    pub fn new(status: StatusCode, code: &'static str, message: String) -> WebError {
+        WebError { status, body: ErrorBody { code, message, details: vec![] } }
+    }
+
+    pub fn not_found(code: &'static str, message: String) -> WebError {
+        WebError::new(StatusCode::NOT_FOUND, code, message)
+    }
+
+    fn internal(code: &'static str) -> WebError {
+        WebError::new(StatusCode::INTERNAL_SERVER_ERROR, code, "internal server error".to_owned())
+    }
+}
+
+impl Display for WebError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.body.message)
+    }
+}
+
+impl ResponseError for WebError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status).json(&self.body)
+    }
+}
+
+impl From<DomainError> for WebError {
+    fn from(e: DomainError) -> Self {
+        let status = StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        if status.is_server_error() {
+            // Messages of DB and internal errors may reveal queries, paths or credentials.
+            error!("{}", e);
+            return WebError { status, ..WebError::internal(e.code()) };
+        }
+        let details =
+            e.details().iter().map(|d| ErrorDetailBody { code: d.code(), message: d.message().to_owned() }).collect();
+        WebError { status, body: ErrorBody { code: e.code(), message: e.to_string(), details } }
+    }
+}
+
+impl From<PoolError> for WebError {
+    fn from(e: PoolError) -> Self {
+        warn!("couldn't get db connection from pool: {}", e);
+        WebError::new(StatusCode::SERVICE_UNAVAILABLE, "pool", "database is unavailable".to_owned())
+    }
+}
+
+impl From<BlockingError> for WebError {
+    fn from(e: BlockingError) -> Self {
+        error!("{}", e);
+        WebError::internal("blocking")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The built-in test attribute, as `#[test]` is shadowed by the async one of actix_web.
+    #[std::prelude::v1::test]
+    fn test_from_domain_error() {
+        let e = WebError::from(DomainError::not_found("group", "No group: foo".to_owned()));
+        assert_eq!(StatusCode::NOT_FOUND, e.status);
+        assert_eq!("No group: foo", e.body.message);
+        assert_eq!(1, e.body.details.len());
+
+        let e = WebError::from(DomainError::internal("db", "password authentication failed".to_owned()));
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, e.status);
+        assert_eq!("db", e.body.code);
+        assert_eq!("internal server error", e.body.message);
+        assert!(e.body.details.is_empty());
+    }
+}
@@ -6,35 +6,51 @@ extern crate log;
 use std::{
     collections::{HashMap, HashSet},
     env,
+    time::Duration,
 };
 
 use actix_web::{
-    error, middleware,
+    http::{header, StatusCode},
+    middleware,
     web::{self, Data},
-    App, HttpResponse, HttpServer, Responder,
+    App, HttpRequest, HttpResponse, HttpServer, Responder, ResponseError,
 };
-use chrono::NaiveDateTime;
-use diesel::r2d2::{self, ConnectionManager};
+use chrono::{NaiveDateTime, Utc};
+use diesel::r2d2;
 use ichnome::{
-    db::Connection,
-    db::{OmFootprints, OmGroups, OmHistories, OmStats, OmWorkspaces, StatOrder, StatSearchCondition},
-    error::DomainError,
-    Footprint, Group, History, Stat, Status, Workspace, META_GROUP_NAME,
+    action::{self, select_content_bodies, PushRequest},
+    db::{
+        tokenize_path, AttrCursor, HistoryCursor, HistorySearchCondition, OmAttrs, OmFootprints, OmGroups, OmHistories,
+        OmStats, OmWorkspaces, SnapshotCondition, SnapshotCursor, StatCursor, StatOrder, StatSearchCondition,
+    },
+    db::{Connection, ConnectionManager},
+    error::{DomainError, DomainResult},
+    Attr, Changeset, Footprint, Group, History, PushCursor, PushResult, Stat, Status, Workspace, META_GROUP_NAME,
 };
 use serde::{Deserialize, Serialize};
 use structopt::{clap, StructOpt};
 
-use crate::models::{WebHistory, WebStat};
+use crate::{
+    error::{WebError, WebResult},
+    models::{WebAttr, WebHistory, WebStat},
+};
+
+type DbPool = r2d2::Pool<ConnectionManager>;
 
-type DbPool = r2d2::Pool<ConnectionManager<Connection>>;
+const PUSH_PAYLOAD_SIZE_MAX: usize = 64 * 1024 * 1024;
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 1000;
+const DIGEST_LEN: usize = 64;
+const DIGEST_PREFIX_LEN_MIN: usize = 4;
 
+mod error;
 mod models;
 
 fn find_workspace_and_group(
     conn: &mut Connection,
     workspace_name: &str,
     group_name: &str,
-) -> Result<Option<(Workspace, Group)>, Box<dyn std::error::Error>> {
+) -> DomainResult<Option<(Workspace, Group)>> {
     let workspace = OmWorkspaces::find_by_name(conn, workspace_name)?;
     if let Some(workspace) = workspace {
         let group = OmGroups::find_by_name(conn, workspace.id, group_name)?;
@@ -48,15 +64,104 @@ fn find_workspace_and_group(
     }
 }
 
+fn page_limit(limit: Option<i64>) -> DomainResult<i64> {
+    match limit {
+        Some(limit) if limit <= 0 => Err(DomainError::params("limit", format!("limit must be positive: {}", limit))),
+        Some(limit) if limit > MAX_LIMIT => {
+            Err(DomainError::params("limit", format!("limit must be at most {}: {}", MAX_LIMIT, limit)))
+        }
+        Some(limit) => Ok(limit),
+        None => Ok(DEFAULT_LIMIT),
+    }
+}
+
+fn split_page<T>(mut items: Vec<T>, limit: i64) -> (Vec<T>, bool) {
+    let has_next = items.len() as i64 > limit;
+    items.truncate(limit as usize);
+    (items, has_next)
+}
+
+fn next_link(req: &HttpRequest, cursor: &str) -> String {
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    for (k, v) in url::form_urlencoded::parse(req.query_string().as_bytes()).filter(|(k, _)| k != "cursor") {
+        query.append_pair(&k, &v);
+    }
+    query.append_pair("cursor", cursor);
+    format!("{}?{}", req.path(), query.finish())
+}
+
+fn parse_stat_order(order: Option<&str>) -> DomainResult<StatOrder> {
+    match order {
+        None | Some("-updated_at") => Ok(StatOrder::UpdatedAtDesc),
+        Some("updated_at") => Ok(StatOrder::UpdatedAtAsc),
+        Some("path") => Ok(StatOrder::PathAsc),
+        Some("-path") => Ok(StatOrder::PathDesc),
+        Some("size") => Ok(StatOrder::SizeAsc),
+        Some("-size") => Ok(StatOrder::SizeDesc),
+        Some(order) => Err(DomainError::params("order", format!("invalid order: {}", order))),
+    }
+}
+
+fn parse_statuses(statuses: Option<&str>) -> DomainResult<Vec<Status>> {
+    let statuses = if let Some(statuses) = statuses {
+        statuses
+    } else {
+        return Ok(vec![Status::Enabled]);
+    };
+    statuses
+        .split(',')
+        .map(|s| match s.trim().to_ascii_lowercase().as_str() {
+            "0" | "disabled" => Ok(Status::Disabled),
+            "1" | "enabled" => Ok(Status::Enabled),
+            s => Err(DomainError::params("status", format!("invalid status: {}", s))),
+        })
+        .collect()
+}
+
+fn validate_digest_prefix(digest_prefix: &str) -> DomainResult<&str> {
+    if digest_prefix.len() <= DIGEST_LEN
+        && digest_prefix.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    {
+        Ok(digest_prefix)
+    } else {
+        Err(DomainError::params("digest_prefix", format!("invalid digest prefix: {}", digest_prefix)))
+    }
+}
+
+fn find_footprint_by_digest_prefix(conn: &mut Connection, digest: &str) -> DomainResult<Option<Footprint>> {
+    if digest.len() == DIGEST_LEN {
+        return OmFootprints::find_by_digest(conn, digest);
+    }
+    if digest.len() < DIGEST_PREFIX_LEN_MIN {
+        return Err(DomainError::params(
+            "digest",
+            format!("digest prefix must be at least {} characters: {}", DIGEST_PREFIX_LEN_MIN, digest),
+        ));
+    }
+    let mut footprints = OmFootprints::select_by_digest_prefix(conn, validate_digest_prefix(digest)?, 2)?;
+    if footprints.len() > 1 {
+        return Err(DomainError::conflict("digest", format!("ambiguous digest prefix: {}", digest)));
+    }
+    Ok(footprints.pop())
+}
+
 #[derive(Deserialize)]
 struct GetStatsQuery {
     path_prefix: Option<String>,
     path_partial: Option<String>,
+    glob: Option<String>,
+    ext: Option<String>,
+    digest_prefix: Option<String>,
     status: Option<String>,
     mtime_after: Option<NaiveDateTime>,
     mtime_before: Option<NaiveDateTime>,
+    size_min: Option<i64>,
+    size_max: Option<i64>,
     updated_at_after: Option<NaiveDateTime>,
     updated_at_before: Option<NaiveDateTime>,
+    order: Option<String>,
+    as_of: Option<NaiveDateTime>,
+    cursor: Option<String>,
     limit: Option<i64>,
 }
 
@@ -66,6 +171,7 @@ struct GetStatsResponse {
     group: Group,
     stats: Vec<Stat>,
     stats_count: i64,
+    next: Option<String>,
 }
 
 fn get_stats_impl(
@@ -73,39 +179,80 @@ fn get_stats_impl(
     workspace_name: &str,
     group_name: &str,
     q: &GetStatsQuery,
-) -> Result<Option<GetStatsResponse>, Box<dyn std::error::Error>> {
+) -> DomainResult<Option<GetStatsResponse>> {
     let pair = find_workspace_and_group(conn, workspace_name, group_name)?;
     if let Some((workspace, group)) = pair {
-        let status = q
-            .status
-            .as_ref()
-            .map(|s| match s.to_ascii_lowercase().as_str() {
-                "0" | "disabled" => Some(Status::Disabled),
-                "1" | "enabled" => Some(Status::Enabled),
-                _ => None,
-            })
-            .flatten()
-            .unwrap_or(Status::Enabled);
         let count_cond = StatSearchCondition {
             group_ids: Some(vec![group.id]),
             path_prefix: q.path_prefix.as_ref().map(|s| s.as_ref()),
             path_partial: q.path_partial.as_ref().map(|s| s.as_ref()),
-            statuses: Some(vec![status]),
+            path_glob: q.glob.as_deref(),
+            extensions: q.ext.as_deref().map(|s| s.split(',').filter(|e| !e.is_empty()).collect()),
+            digest_prefix: q.digest_prefix.as_deref().map(validate_digest_prefix).transpose()?,
+            statuses: Some(parse_statuses(q.status.as_deref())?),
             mtime_after: q.mtime_after,
             mtime_before: q.mtime_before,
+            size_min: q.size_min,
+            size_max: q.size_max,
             updated_at_after: q.updated_at_after,
             updated_at_before: q.updated_at_before,
             ..Default::default()
         };
+        let order = parse_stat_order(q.order.as_deref())?;
+        let after = q.cursor.as_deref().map(StatCursor::decode).transpose()?;
+        let limit = page_limit(q.limit)?;
         let stats_count = OmStats::count(conn, workspace.id, &count_cond)?;
-        let cond = StatSearchCondition {
-            order: Some(StatOrder::UpdatedAtDesc),
-            limit: Some(q.limit.unwrap_or(100)),
-            ..count_cond
-        };
+        let cond = StatSearchCondition { order: Some(order), after, limit: Some(limit + 1), ..count_cond };
         debug!("search condition: {:?}", &cond);
-        let stats = OmStats::search(conn, workspace.id, &cond)?;
-        Ok(Some(GetStatsResponse { workspace, group, stats, stats_count }))
+        let (stats, has_next) = split_page(OmStats::search(conn, workspace.id, &cond)?, limit);
+        let next = stats.last().filter(|_| has_next).map(|s| StatCursor::new(s).encode());
+        Ok(Some(GetStatsResponse { workspace, group, stats, stats_count, next }))
+    } else {
+        Ok(None)
+    }
+}
+
+#[derive(Serialize)]
+struct GetSnapshotResponse {
+    workspace: Workspace,
+    group: Group,
+    as_of: NaiveDateTime,
+    histories: Vec<History>,
+    next: Option<String>,
+}
+
+fn get_snapshot_impl(
+    conn: &mut Connection,
+    workspace_name: &str,
+    group_name: &str,
+    as_of: NaiveDateTime,
+    q: &GetStatsQuery,
+) -> DomainResult<Option<GetSnapshotResponse>> {
+    let unsupported = [
+        ("path_partial", q.path_partial.is_some()),
+        ("glob", q.glob.is_some()),
+        ("ext", q.ext.is_some()),
+        ("digest_prefix", q.digest_prefix.is_some()),
+        ("status", q.status.is_some()),
+        ("mtime_after", q.mtime_after.is_some()),
+        ("mtime_before", q.mtime_before.is_some()),
+        ("size_min", q.size_min.is_some()),
+        ("size_max", q.size_max.is_some()),
+        ("updated_at_after", q.updated_at_after.is_some()),
+        ("updated_at_before", q.updated_at_before.is_some()),
+        ("order", q.order.is_some()),
+    ];
+    if let Some((name, _)) = unsupported.iter().find(|(_, present)| *present) {
+        return Err(DomainError::params("as_of", format!("as_of can't be combined with {}", name)));
+    }
+    let pair = find_workspace_and_group(conn, workspace_name, group_name)?;
+    if let Some((workspace, group)) = pair {
+        let after = q.cursor.as_deref().map(SnapshotCursor::decode).transpose()?;
+        let limit = page_limit(q.limit)?;
+        let cond = SnapshotCondition { path_prefix: q.path_prefix.as_deref(), after, limit: Some(limit + 1) };
+        let (histories, has_next) = split_page(OmHistories::snapshot(conn, group.id, as_of, &cond)?, limit);
+        let next = histories.last().filter(|_| has_next).map(|h| SnapshotCursor::new(h).encode());
+        Ok(Some(GetSnapshotResponse { workspace, group, as_of, histories, next }))
     } else {
         Ok(None)
     }
@@ -114,24 +261,33 @@ fn get_stats_impl(
 #[get("/{workspace_name}/stats/{group_name}")]
 async fn get_stats(
     pool: web::Data<DbPool>,
+    req: HttpRequest,
     path_params: web::Path<(String, String)>,
     q: web::Query<GetStatsQuery>,
-) -> actix_web::Result<impl Responder> {
+) -> WebResult<impl Responder> {
     let (workspace_name, group_name) = path_params.into_inner();
     let group_name_2 = group_name.clone();
     let q = q.into_inner();
-    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let mut conn = pool.get()?;
+
+    if let Some(as_of) = q.as_of {
+        let resp = web::block(move || get_snapshot_impl(&mut conn, &workspace_name, &group_name, as_of, &q)).await??;
+        return match resp {
+            Some(mut resp) => {
+                resp.next = resp.next.map(|cursor| next_link(&req, &cursor));
+                Ok(HttpResponse::Ok().json(&resp))
+            }
+            None => Err(WebError::not_found("group", format!("No group: {}", &group_name_2))),
+        };
+    }
 
-    let resp =
-        web::block(move || get_stats_impl(&mut conn, &workspace_name, &group_name, &q).map_err(|e| e.to_string()))
-            .await?
-            .map_err(error::ErrorInternalServerError)?;
+    let resp = web::block(move || get_stats_impl(&mut conn, &workspace_name, &group_name, &q)).await??;
     match resp {
-        Some(resp) => Ok(HttpResponse::Ok().json(&resp)),
-        None => {
-            let res = HttpResponse::NotFound().body(format!("No group: {}", &group_name_2));
-            Ok(res)
+        Some(mut resp) => {
+            resp.next = resp.next.map(|cursor| next_link(&req, &cursor));
+            Ok(HttpResponse::Ok().json(&resp))
         }
+        None => Err(WebError::not_found("group", format!("No group: {}", &group_name_2))),
     }
 }
 
@@ -145,21 +301,21 @@ struct GetStatResponse {
     eq_stats: Option<Vec<WebStat>>,
 }
 
-fn to_web_stats(workspace: &Workspace, group_map: &HashMap<i32, &Group>, stats: &Vec<Stat>) -> Vec<WebStat> {
+fn to_web_stats(workspace: &Workspace, group_map: &HashMap<i32, &Group>, stats: &[Stat]) -> Vec<WebStat> {
     stats
         .iter()
         .map(|s| (s, group_map.get(&s.group_id)))
         .filter(|(_, g)| g.is_some())
-        .map(|(s, g)| WebStat::from(&workspace, *g.unwrap(), s))
+        .map(|(s, g)| WebStat::from(workspace, g.unwrap(), s))
         .collect()
 }
 
-fn to_web_histories(workspace: &Workspace, group_map: &HashMap<i32, &Group>, stats: &Vec<History>) -> Vec<WebHistory> {
+fn to_web_histories(workspace: &Workspace, group_map: &HashMap<i32, &Group>, stats: &[History]) -> Vec<WebHistory> {
     stats
         .iter()
         .map(|h| (h, group_map.get(&h.group_id)))
         .filter(|(_, g)| g.is_some())
-        .map(|(h, g)| WebHistory::from(&workspace, *g.unwrap(), h))
+        .map(|(h, g)| WebHistory::from(workspace, g.unwrap(), h))
         .collect()
 }
 
@@ -168,7 +324,7 @@ fn get_stat_impl(
     workspace_name: &str,
     group_name: &str,
     path: &str,
-) -> Result<Option<GetStatResponse>, Box<dyn std::error::Error>> {
+) -> DomainResult<Option<GetStatResponse>> {
     let pair = find_workspace_and_group(conn, workspace_name, group_name)?;
     if let Some((workspace, group)) = pair {
         let stat = OmStats::find_by_path(conn, group.id, path)?;
@@ -217,23 +373,85 @@ fn get_stat_impl(
 async fn get_stat(
     pool: web::Data<DbPool>,
     path_params: web::Path<(String, String, String)>,
-) -> actix_web::Result<impl Responder> {
+) -> WebResult<impl Responder> {
     let (workspace_name, group_name, path) = path_params.into_inner();
     let group_name_2 = group_name.clone();
     let path_2 = path.clone();
-    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let mut conn = pool.get()?;
 
-    let resp =
-        web::block(move || get_stat_impl(&mut conn, &workspace_name, &group_name, &path).map_err(|e| e.to_string()))
-            .await?
-            .map_err(error::ErrorInternalServerError)?;
+    let resp = web::block(move || get_stat_impl(&mut conn, &workspace_name, &group_name, &path)).await??;
 
     match resp {
         Some(resp) => Ok(HttpResponse::Ok().json(&resp)),
-        None => {
-            let res = HttpResponse::NotFound().body(format!("No stat: {}/{}", &group_name_2, &path_2));
-            Ok(res)
+        None => Err(WebError::not_found("stat", format!("No stat: {}/{}", &group_name_2, &path_2))),
+    }
+}
+
+#[derive(Deserialize)]
+struct GetHistoriesQuery {
+    path_prefix: Option<String>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+impl GetHistoriesQuery {
+    fn to_condition(&self) -> DomainResult<(HistorySearchCondition<'_>, i64)> {
+        let limit = page_limit(self.limit)?;
+        let cond = HistorySearchCondition {
+            path_prefix: self.path_prefix.as_deref(),
+            after: self.cursor.as_deref().map(HistoryCursor::decode).transpose()?,
+            limit: Some(limit + 1),
+            ..Default::default()
+        };
+        Ok((cond, limit))
+    }
+}
+
+#[derive(Serialize)]
+struct GetHistoriesResponse {
+    workspace: Workspace,
+    group: Group,
+    histories: Vec<History>,
+    next: Option<String>,
+}
+
+fn get_histories_impl(
+    conn: &mut Connection,
+    workspace_name: &str,
+    group_name: &str,
+    q: &GetHistoriesQuery,
+) -> DomainResult<Option<GetHistoriesResponse>> {
+    let pair = find_workspace_and_group(conn, workspace_name, group_name)?;
+    if let Some((workspace, group)) = pair {
+        let (cond, limit) = q.to_condition()?;
+        let cond = HistorySearchCondition { group_ids: Some(vec![group.id]), ..cond };
+        let (histories, has_next) = split_page(OmHistories::search(conn, workspace.id, &cond)?, limit);
+        let next = histories.last().filter(|_| has_next).map(|h| HistoryCursor::new(h).encode());
+        Ok(Some(GetHistoriesResponse { workspace, group, histories, next }))
+    } else {
+        Ok(None)
+    }
+}
+
+#[get("/{workspace_name}/histories/{group_name}")]
+async fn get_histories(
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+    path_params: web::Path<(String, String)>,
+    q: web::Query<GetHistoriesQuery>,
+) -> WebResult<impl Responder> {
+    let (workspace_name, group_name) = path_params.into_inner();
+    let group_name_2 = group_name.clone();
+    let q = q.into_inner();
+    let mut conn = pool.get()?;
+
+    let resp = web::block(move || get_histories_impl(&mut conn, &workspace_name, &group_name, &q)).await??;
+    match resp {
+        Some(mut resp) => {
+            resp.next = resp.next.map(|cursor| next_link(&req, &cursor));
+            Ok(HttpResponse::Ok().json(&resp))
         }
+        None => Err(WebError::not_found("group", format!("No group: {}", &group_name_2))),
     }
 }
 
@@ -250,14 +468,14 @@ fn get_footprint_impl(
     conn: &mut Connection,
     workspace_name: &str,
     digest: &str,
-) -> Result<Option<GetFootprintResponse>, Box<dyn std::error::Error>> {
+) -> DomainResult<Option<GetFootprintResponse>> {
     let workspace = OmWorkspaces::find_by_name(conn, workspace_name)?;
     let workspace = if let Some(workspace) = workspace {
         workspace
     } else {
         return Ok(None);
     };
-    let footprint = OmFootprints::find_by_digest(conn, digest)?;
+    let footprint = find_footprint_by_digest_prefix(conn, digest)?;
     let group_name: Option<String> = None;
     if let Some(footprint) = footprint {
         let mut group_ids = HashSet::new();
@@ -290,24 +508,213 @@ fn get_footprint_impl(
 }
 
 #[get("/{workspace_name}/footprints/{digest}")]
-async fn get_footprint(
+async fn get_footprint(pool: web::Data<DbPool>, path_params: web::Path<(String, String)>) -> WebResult<impl Responder> {
+    let (workspace_name, digest) = path_params.into_inner();
+    let digest_2 = digest.clone();
+    let mut conn = pool.get()?;
+
+    let resp = web::block(move || get_footprint_impl(&mut conn, &workspace_name, &digest)).await??;
+
+    match resp {
+        Some(resp) => Ok(HttpResponse::Ok().json(&resp)),
+        None => Err(WebError::not_found("footprint", format!("No footprint: {}", digest_2))),
+    }
+}
+
+#[derive(Serialize)]
+struct GetFootprintHistoriesResponse {
+    workspace: Workspace,
+    footprint: Footprint,
+    histories: Vec<WebHistory>,
+    next: Option<String>,
+}
+
+fn get_footprint_histories_impl(
+    conn: &mut Connection,
+    workspace_name: &str,
+    digest: &str,
+    q: &GetHistoriesQuery,
+) -> DomainResult<Option<GetFootprintHistoriesResponse>> {
+    let workspace = OmWorkspaces::find_by_name(conn, workspace_name)?;
+    let workspace = if let Some(workspace) = workspace {
+        workspace
+    } else {
+        return Ok(None);
+    };
+    let footprint = find_footprint_by_digest_prefix(conn, digest)?;
+    if let Some(footprint) = footprint {
+        let (cond, limit) = q.to_condition()?;
+        let cond = HistorySearchCondition { footprint_id: Some(footprint.id), ..cond };
+        let (histories, has_next) = split_page(OmHistories::search(conn, workspace.id, &cond)?, limit);
+        let next = histories.last().filter(|_| has_next).map(|h| HistoryCursor::new(h).encode());
+        let group_ids: Vec<i32> = histories.iter().map(|h| h.group_id).collect::<HashSet<_>>().into_iter().collect();
+        let groups = OmGroups::select(conn, &group_ids)?;
+        let group_map = groups.iter().map(|g| (g.id, g)).collect();
+        let histories = to_web_histories(&workspace, &group_map, &histories);
+        Ok(Some(GetFootprintHistoriesResponse { workspace, footprint, histories, next }))
+    } else {
+        Ok(None)
+    }
+}
+
+#[get("/{workspace_name}/footprints/{digest}/histories")]
+async fn get_footprint_histories(
     pool: web::Data<DbPool>,
+    req: HttpRequest,
     path_params: web::Path<(String, String)>,
-) -> actix_web::Result<impl Responder> {
+    q: web::Query<GetHistoriesQuery>,
+) -> WebResult<impl Responder> {
     let (workspace_name, digest) = path_params.into_inner();
     let digest_2 = digest.clone();
-    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let q = q.into_inner();
+    let mut conn = pool.get()?;
+
+    let resp = web::block(move || get_footprint_histories_impl(&mut conn, &workspace_name, &digest, &q)).await??;
+    match resp {
+        Some(mut resp) => {
+            resp.next = resp.next.map(|cursor| next_link(&req, &cursor));
+            Ok(HttpResponse::Ok().json(&resp))
+        }
+        None => Err(WebError::not_found("footprint", format!("No footprint: {}", digest_2))),
+    }
+}
+
+fn to_web_attrs(conn: &mut Connection, workspace: &Workspace, attrs: &[Attr]) -> DomainResult<Vec<WebAttr>> {
+    let footprint_ids: Vec<i32> =
+        attrs.iter().map(|a| a.value_footprint_id).collect::<HashSet<_>>().into_iter().collect();
+    let bodies = select_content_bodies(conn, &footprint_ids)?;
+    Ok(attrs
+        .iter()
+        .map(|a| WebAttr::from(workspace, a, bodies.get(&a.value_footprint_id).map(Vec::as_slice)))
+        .collect())
+}
 
-    let resp = web::block(move || get_footprint_impl(&mut conn, &workspace_name, &digest).map_err(|e| e.to_string()))
-        .await?
-        .map_err(error::ErrorInternalServerError)?;
+fn select_enabled_web_stats(
+    conn: &mut Connection,
+    workspace: &Workspace,
+    footprint_ids: &[i32],
+) -> DomainResult<HashMap<i32, Vec<WebStat>>> {
+    let stats: Vec<Stat> = OmStats::select_by_footprint_ids(conn, workspace.id, footprint_ids)?
+        .into_iter()
+        .filter(|s| s.status == Status::Enabled)
+        .collect();
+    let group_ids: Vec<i32> = stats.iter().map(|s| s.group_id).collect::<HashSet<_>>().into_iter().collect();
+    let groups = OmGroups::select(conn, &group_ids)?;
+    let group_map = groups.iter().map(|g| (g.id, g)).collect();
+    let mut stats_map: HashMap<i32, Vec<Stat>> = HashMap::new();
+    for stat in stats {
+        if let Some(footprint_id) = stat.footprint_id {
+            stats_map.entry(footprint_id).or_default().push(stat);
+        }
+    }
+    Ok(stats_map.into_iter().map(|(id, stats)| (id, to_web_stats(workspace, &group_map, &stats))).collect())
+}
+
+#[derive(Serialize)]
+struct GetFootprintAttrsResponse {
+    workspace: Workspace,
+    footprint: Footprint,
+    attrs: Vec<WebAttr>,
+    stats: Vec<WebStat>,
+}
+
+fn get_footprint_attrs_impl(
+    conn: &mut Connection,
+    workspace_name: &str,
+    digest: &str,
+) -> DomainResult<Option<GetFootprintAttrsResponse>> {
+    let workspace = OmWorkspaces::find_by_name(conn, workspace_name)?;
+    let workspace = if let Some(workspace) = workspace { workspace } else { return Ok(None) };
+    let footprint = find_footprint_by_digest_prefix(conn, digest)?;
+    let footprint = if let Some(footprint) = footprint { footprint } else { return Ok(None) };
+    let attrs: Vec<Attr> = OmAttrs::select_by_target_footprint_id(conn, workspace.id, footprint.id)?
+        .into_iter()
+        .filter(|a| a.status == Status::Enabled)
+        .collect();
+    let attrs = to_web_attrs(conn, &workspace, &attrs)?;
+    let stats = select_enabled_web_stats(conn, &workspace, &[footprint.id])?.remove(&footprint.id).unwrap_or_default();
+    Ok(Some(GetFootprintAttrsResponse { workspace, footprint, attrs, stats }))
+}
+
+#[get("/{workspace_name}/footprints/{digest}/attrs")]
+async fn get_footprint_attrs(
+    pool: web::Data<DbPool>,
+    path_params: web::Path<(String, String)>,
+) -> WebResult<impl Responder> {
+    let (workspace_name, digest) = path_params.into_inner();
+    let digest_2 = digest.clone();
+    let mut conn = pool.get()?;
+
+    let resp = web::block(move || get_footprint_attrs_impl(&mut conn, &workspace_name, &digest)).await??;
 
     match resp {
         Some(resp) => Ok(HttpResponse::Ok().json(&resp)),
-        None => {
-            let res = HttpResponse::NotFound().body(format!("No footprint: {}", digest_2));
-            Ok(res)
+        None => Err(WebError::not_found("footprint", format!("No footprint: {}", digest_2))),
+    }
+}
+
+#[derive(Deserialize)]
+struct GetAttrsQuery {
+    key: String,
+    summary: Option<String>,
+    limit: Option<i64>,
+    cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+struct GetAttrsResponse {
+    workspace: Workspace,
+    attrs: Vec<WebAttr>,
+    stats: HashMap<String, Vec<WebStat>>,
+    next: Option<String>,
+}
+
+fn get_attrs_impl(
+    conn: &mut Connection,
+    workspace_name: &str,
+    q: &GetAttrsQuery,
+) -> DomainResult<Option<GetAttrsResponse>> {
+    let workspace = OmWorkspaces::find_by_name(conn, workspace_name)?;
+    let workspace = if let Some(workspace) = workspace { workspace } else { return Ok(None) };
+    let limit = page_limit(q.limit)?;
+    let after = q.cursor.as_deref().map(AttrCursor::decode).transpose()?;
+    let attrs = OmAttrs::search(conn, workspace.id, &q.key, q.summary.as_deref(), after.as_ref(), limit + 1)?;
+    let (attrs, has_next) = split_page(attrs, limit);
+    let next = attrs.last().filter(|_| has_next).map(|a| AttrCursor::new(a).encode());
+    let footprint_ids: Vec<i32> =
+        attrs.iter().map(|a| a.target_footprint_id).collect::<HashSet<_>>().into_iter().collect();
+    let mut stats_map = select_enabled_web_stats(conn, &workspace, &footprint_ids)?;
+    let mut stats = HashMap::new();
+    for attr in attrs.iter() {
+        if !stats.contains_key(&attr.target_digest) {
+            let web_stats = stats_map.remove(&attr.target_footprint_id).unwrap_or_default();
+            stats.insert(attr.target_digest.clone(), web_stats);
+        }
+    }
+    let attrs = to_web_attrs(conn, &workspace, &attrs)?;
+    Ok(Some(GetAttrsResponse { workspace, attrs, stats, next }))
+}
+
+#[get("/{workspace_name}/attrs")]
+async fn get_attrs(
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+    path_params: web::Path<(String,)>,
+    q: web::Query<GetAttrsQuery>,
+) -> WebResult<impl Responder> {
+    let (workspace_name,) = path_params.into_inner();
+    let workspace_name_2 = workspace_name.clone();
+    let q = q.into_inner();
+    let mut conn = pool.get()?;
+
+    let resp = web::block(move || get_attrs_impl(&mut conn, &workspace_name, &q)).await??;
+
+    match resp {
+        Some(mut resp) => {
+            resp.next = resp.next.map(|cursor| next_link(&req, &cursor));
+            Ok(HttpResponse::Ok().json(&resp))
         }
+        None => Err(WebError::not_found("workspace", format!("No workspace: {}", &workspace_name_2))),
     }
 }
 
@@ -317,10 +724,7 @@ struct GetGroupsResponse {
     groups: Vec<Group>,
 }
 
-fn get_groups_impl(
-    conn: &mut Connection,
-    workspace_name: &str,
-) -> Result<Option<GetGroupsResponse>, Box<dyn std::error::Error>> {
+fn get_groups_impl(conn: &mut Connection, workspace_name: &str) -> DomainResult<Option<GetGroupsResponse>> {
     let workspace = OmWorkspaces::find_by_name(conn, workspace_name)?;
     if let Some(workspace) = workspace {
         let groups = OmGroups::select_all(conn, workspace.id)?;
@@ -331,20 +735,15 @@ fn get_groups_impl(
 }
 
 #[get("/{workspace_name}/groups")]
-async fn get_groups(pool: web::Data<DbPool>, path_params: web::Path<(String,)>) -> actix_web::Result<impl Responder> {
+async fn get_groups(pool: web::Data<DbPool>, path_params: web::Path<(String,)>) -> WebResult<impl Responder> {
     let (workspace_name,) = path_params.into_inner();
-    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let mut conn = pool.get()?;
 
-    let resp = web::block(move || get_groups_impl(&mut conn, &workspace_name).map_err(|e| e.to_string()))
-        .await?
-        .map_err(error::ErrorInternalServerError)?;
+    let resp = web::block(move || get_groups_impl(&mut conn, &workspace_name)).await??;
 
     match resp {
         Some(resp) => Ok(HttpResponse::Ok().json(&resp)),
-        None => {
-            let res = HttpResponse::NotFound().body("No groups");
-            Ok(res)
-        }
+        None => Err(WebError::not_found("group", "No groups".to_owned())),
     }
 }
 
@@ -361,7 +760,7 @@ fn get_group_impl(
     conn: &mut Connection,
     workspace_name: &str,
     group_name: &str,
-) -> Result<Option<GetGroupResponse>, Box<dyn std::error::Error>> {
+) -> DomainResult<Option<GetGroupResponse>> {
     let pair = find_workspace_and_group(conn, workspace_name, group_name)?;
     if let Some((workspace, group)) = pair {
         let meta_group = OmGroups::find_by_name(conn, group.workspace_id, META_GROUP_NAME)?;
@@ -396,24 +795,16 @@ fn get_group_impl(
 }
 
 #[get("/{workspace_name}/groups/{group_name}")]
-async fn get_group(
-    pool: web::Data<DbPool>,
-    path_params: web::Path<(String, String)>,
-) -> actix_web::Result<impl Responder> {
+async fn get_group(pool: web::Data<DbPool>, path_params: web::Path<(String, String)>) -> WebResult<impl Responder> {
     let (workspace_name, group_name) = path_params.into_inner();
     let group_name_2 = group_name.clone();
-    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let mut conn = pool.get()?;
 
-    let resp = web::block(move || get_group_impl(&mut conn, &workspace_name, &group_name).map_err(|e| e.to_string()))
-        .await?
-        .map_err(error::ErrorInternalServerError)?;
+    let resp = web::block(move || get_group_impl(&mut conn, &workspace_name, &group_name)).await??;
 
     match resp {
         Some(resp) => Ok(HttpResponse::Ok().json(&resp)),
-        None => {
-            let res = HttpResponse::NotFound().body(format!("No group: {}", &group_name_2));
-            Ok(res)
-        }
+        None => Err(WebError::not_found("group", format!("No group: {}", &group_name_2))),
     }
 }
 
@@ -440,14 +831,14 @@ fn get_diff_impl_search_stats(
     workspace: &Workspace,
     group_name: &str,
     path_prefix: &str,
-) -> Result<Option<(Group, Vec<Stat>)>, Box<dyn std::error::Error>> {
-    let group = OmGroups::find_by_name(conn, workspace.id, &group_name)?;
+) -> DomainResult<Option<(Group, Vec<Stat>)>> {
+    let group = OmGroups::find_by_name(conn, workspace.id, group_name)?;
     let group = if let Some(group) = group { group } else { return Ok(None) };
     let cond =
         StatSearchCondition { group_ids: Some(vec![group.id]), path_prefix: Some(path_prefix), ..Default::default() };
     let stats_count = OmStats::count(conn, workspace.id, &cond)?;
     if stats_count > 1000 {
-        return Err(Box::new(DomainError::params("path_prefix", format!("too many stats: {}", stats_count))));
+        return Err(DomainError::params("path_prefix", format!("too many stats: {}", stats_count)));
     };
     let stats = OmStats::search(conn, workspace.id, &cond)?;
     Ok(Some((group, stats)))
@@ -457,7 +848,7 @@ fn get_diff_impl(
     conn: &mut Connection,
     workspace_name: &str,
     q: &GetDiffQuery,
-) -> Result<Option<GetDiffResponse>, Box<dyn std::error::Error>> {
+) -> DomainResult<Option<GetDiffResponse>> {
     let workspace = OmWorkspaces::find_by_name(conn, workspace_name)?;
     if let Some(workspace) = workspace {
         let result1 = get_diff_impl_search_stats(conn, &workspace, &q.group_name1, &q.path_prefix1)?;
@@ -479,7 +870,7 @@ fn get_diff_impl(
             }
         }
         let footprints: HashMap<i32, Footprint> =
-            OmFootprints::select(conn, &diff.keys().map(|i| *i).collect())?.into_iter().map(|f| (f.id, f)).collect();
+            OmFootprints::select(conn, &diff.keys().copied().collect())?.into_iter().map(|f| (f.id, f)).collect();
         Ok(Some(GetDiffResponse { workspace, group1, group2, diff, stats, footprints }))
     } else {
         Ok(None)
@@ -491,24 +882,166 @@ async fn get_diff(
     pool: web::Data<DbPool>,
     path_params: web::Path<(String,)>,
     q: web::Query<GetDiffQuery>,
-) -> actix_web::Result<impl Responder> {
+) -> WebResult<impl Responder> {
     let (workspace_name,) = path_params.into_inner();
     let q = q.into_inner();
-    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let mut conn = pool.get()?;
 
-    let resp = web::block(move || get_diff_impl(&mut conn, &workspace_name, &q).map_err(|e| e.to_string()))
-        .await?
-        .map_err(error::ErrorInternalServerError)?;
+    let resp = web::block(move || get_diff_impl(&mut conn, &workspace_name, &q)).await??;
 
     match resp {
         Some(resp) => Ok(HttpResponse::Ok().json(&resp)),
-        None => {
-            let res = HttpResponse::NotFound().body(format!("Not found"));
-            Ok(res)
-        }
+        None => Err(WebError::not_found("group", "Not found".to_owned())),
+    }
+}
+
+#[derive(Deserialize)]
+struct GetSearchQuery {
+    q: String,
+    limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct SearchHit {
+    score: f64,
+    #[serde(flatten)]
+    stat: WebStat,
+}
+
+#[derive(Serialize)]
+struct GetSearchResponse {
+    workspace: Workspace,
+    q: String,
+    hits: Vec<SearchHit>,
+}
+
+fn get_search_impl(
+    conn: &mut Connection,
+    workspace_name: &str,
+    q: &GetSearchQuery,
+) -> DomainResult<Option<GetSearchResponse>> {
+    let tokens = tokenize_path(&q.q);
+    if tokens.is_empty() {
+        return Err(DomainError::params("q", "search query is empty".to_owned()));
+    }
+    let limit = page_limit(q.limit)?;
+    let workspace = OmWorkspaces::find_by_name(conn, workspace_name)?;
+    if let Some(workspace) = workspace {
+        let hits = OmStats::search_by_path_tokens(conn, workspace.id, &tokens, limit)?;
+        let group_ids: Vec<i32> = hits.iter().map(|(s, _)| s.group_id).collect::<HashSet<_>>().into_iter().collect();
+        let groups = OmGroups::select(conn, &group_ids)?;
+        let group_map: HashMap<i32, &Group> = groups.iter().map(|g| (g.id, g)).collect();
+        let hits = hits
+            .iter()
+            .map(|(s, score)| SearchHit { score: *score, stat: WebStat::from(&workspace, group_map[&s.group_id], s) })
+            .collect();
+        Ok(Some(GetSearchResponse { workspace, q: q.q.clone(), hits }))
+    } else {
+        Ok(None)
+    }
+}
+
+#[get("/{workspace_name}/search")]
+async fn get_search(
+    pool: web::Data<DbPool>,
+    path_params: web::Path<(String,)>,
+    q: web::Query<GetSearchQuery>,
+) -> WebResult<impl Responder> {
+    let (workspace_name,) = path_params.into_inner();
+    let workspace_name_2 = workspace_name.clone();
+    let q = q.into_inner();
+    let mut conn = pool.get()?;
+
+    let resp = web::block(move || get_search_impl(&mut conn, &workspace_name, &q)).await??;
+
+    match resp {
+        Some(resp) => Ok(HttpResponse::Ok().json(&resp)),
+        None => Err(WebError::not_found("workspace", format!("No workspace: {}", &workspace_name_2))),
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PushToken(Option<String>);
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn check_push_token(req: &HttpRequest, token: &PushToken) -> WebResult<()> {
+    let expected = if let Some(expected) = token.0.as_ref() {
+        expected
+    } else {
+        return Err(WebError::new(StatusCode::FORBIDDEN, "push_token", "Push is disabled".to_owned()));
+    };
+    let actual =
+        req.headers().get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()).and_then(|v| v.strip_prefix("Bearer "));
+    if actual.is_some_and(|actual| constant_time_eq(actual.as_bytes(), expected.as_bytes())) {
+        Ok(())
+    } else {
+        Err(WebError::new(StatusCode::UNAUTHORIZED, "push_token", "Invalid token".to_owned()))
+    }
+}
+
+fn get_push_impl(conn: &mut Connection, workspace_name: &str, group_name: &str) -> DomainResult<Option<PushCursor>> {
+    let mut ctx = action::Context { connection: conn, timer: Box::new(Utc::now) };
+    action::find_push_cursor(&mut ctx, workspace_name, group_name)
+}
+
+#[get("/{workspace_name}/groups/{group_name}/push")]
+async fn get_push(
+    pool: web::Data<DbPool>,
+    token: web::Data<PushToken>,
+    req: HttpRequest,
+    path_params: web::Path<(String, String)>,
+) -> WebResult<impl Responder> {
+    check_push_token(&req, &token)?;
+    let (workspace_name, group_name) = path_params.into_inner();
+    let workspace_name_2 = workspace_name.clone();
+    let mut conn = pool.get()?;
+
+    let resp = web::block(move || get_push_impl(&mut conn, &workspace_name, &group_name)).await??;
+
+    match resp {
+        Some(resp) => Ok(HttpResponse::Ok().json(&resp)),
+        None => Err(WebError::not_found("workspace", format!("No workspace: {}", &workspace_name_2))),
     }
 }
 
+fn post_push_impl(
+    conn: &mut Connection,
+    workspace_name: &str,
+    group_name: &str,
+    changeset: Changeset,
+) -> DomainResult<PushResult> {
+    let mut ctx = action::Context { connection: conn, timer: Box::new(Utc::now) };
+    let req = PushRequest { workspace_name: workspace_name.to_owned(), group_name: group_name.to_owned(), changeset };
+    let resp = action::push(&mut ctx, &req)?;
+    Ok(PushResult {
+        pulled_history_id: resp.group.pulled_history_id,
+        histories: resp.counts.histories,
+        stats: resp.counts.stats,
+        footprints: resp.counts.footprints,
+    })
+}
+
+#[post("/{workspace_name}/groups/{group_name}/push")]
+async fn post_push(
+    pool: web::Data<DbPool>,
+    token: web::Data<PushToken>,
+    req: HttpRequest,
+    path_params: web::Path<(String, String)>,
+    changeset: web::Json<Changeset>,
+) -> WebResult<impl Responder> {
+    check_push_token(&req, &token)?;
+    let (workspace_name, group_name) = path_params.into_inner();
+    let changeset = changeset.into_inner();
+    let mut conn = pool.get()?;
+
+    let resp = web::block(move || post_push_impl(&mut conn, &workspace_name, &group_name, changeset)).await??;
+
+    Ok(HttpResponse::Ok().json(&resp))
+}
+
 #[derive(Debug, StructOpt)]
 #[structopt(name = "ichnome-web")]
 #[structopt(long_version(option_env!("LONG_VERSION").unwrap_or(env!("CARGO_PKG_VERSION"))))]
@@ -516,6 +1049,40 @@ async fn get_diff(
 pub struct Opt {
     #[structopt(short, long, default_value = "127.0.0.1:3024")]
     pub address: String,
+
+    #[structopt(long, env = "ICHNOME_PUSH_TOKEN", hide_env_values = true)]
+    pub push_token: Option<String>,
+
+    #[structopt(long, default_value = "10", name = "N")]
+    pub pool_size: u32,
+
+    #[structopt(long, default_value = "30", name = "SECS")]
+    pub pool_timeout: u64,
+}
+
+fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(
+        web::JsonConfig::default()
+            .limit(PUSH_PAYLOAD_SIZE_MAX)
+            .error_handler(|e, _| WebError::new(e.status_code(), "json", e.to_string()).into()),
+    )
+    .app_data(
+        web::QueryConfig::default()
+            .error_handler(|e, _| WebError::new(StatusCode::BAD_REQUEST, "query", e.to_string()).into()),
+    )
+    .service(get_stats)
+    .service(get_stat)
+    .service(get_histories)
+    .service(get_footprint)
+    .service(get_footprint_histories)
+    .service(get_footprint_attrs)
+    .service(get_attrs)
+    .service(get_groups)
+    .service(get_group)
+    .service(get_diff)
+    .service(get_search)
+    .service(get_push)
+    .service(post_push);
 }
 
 #[actix_rt::main]
@@ -525,21 +1092,313 @@ async fn main() -> std::io::Result<()> {
     let opt = Opt::from_args();
 
     let database_url = env::var("DATABASE_URL").unwrap();
-    let manager = ConnectionManager::<Connection>::new(database_url);
-    let pool = r2d2::Pool::builder().build(manager).expect("Failed to create pool.");
+    let manager = ConnectionManager::new(database_url);
+    let pool = r2d2::Pool::builder()
+        .max_size(opt.pool_size)
+        .connection_timeout(Duration::from_secs(opt.pool_timeout))
+        .build(manager)
+        .expect("Failed to create pool.");
+    let push_token = PushToken(opt.push_token.clone());
 
     HttpServer::new(move || {
         App::new()
             .app_data(Data::new(pool.clone()))
+            .app_data(Data::new(push_token.clone()))
             .wrap(middleware::Logger::default())
-            .service(get_stats)
-            .service(get_stat)
-            .service(get_footprint)
-            .service(get_groups)
-            .service(get_group)
-            .service(get_diff)
+            .configure(configure)
     })
     .bind(&opt.address)?
     .run()
     .await
 }
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use std::path::Path;
+
+    use actix_web::test;
+    use diesel::connection::SimpleConnection;
+    use ichnome::{
+        db::{actions, establish, migrate},
+        ContentType, GroupType, DEFAULT_WORKSPACE_NAME,
+    };
+    use serde_json::Value;
+    use url::Url;
+
+    use super::*;
+
+    fn now() -> NaiveDateTime {
+        NaiveDateTime::parse_from_str("2020-01-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn setup_db(dir: &Path) -> (DbPool, Connection) {
+        let database_url = dir.join("ichnome.db").to_str().unwrap().to_owned();
+        let mut conn = establish(&database_url).unwrap();
+        migrate(&mut conn).unwrap();
+        let pool = r2d2::Pool::builder().max_size(2).build(ConnectionManager::new(database_url)).unwrap();
+        (pool, conn)
+    }
+
+    fn create_file(conn: &mut Connection, group: &Group, path: &str, digest: &str) -> Footprint {
+        let footprint = actions::create_footprint_if_needed(conn, digest, 1, 0, now()).unwrap();
+        actions::update_stat_with_footprint_if_needed(conn, group, path, &footprint, now(), now()).unwrap();
+        footprint
+    }
+
+    fn set_attr(conn: &mut Connection, workspace: &Workspace, target: &Footprint, key: &str, value: &str) -> Attr {
+        let encoder = actions::find_content_encoder(conn, 3).unwrap();
+        let attr_value = actions::AttrValue {
+            key,
+            value: value.as_bytes(),
+            value_content_type: ContentType::Json,
+            value_summary: None,
+        };
+        actions::create_attr_and_stat_with_bytes_if_needed(conn, workspace, target, &attr_value, &encoder, now())
+            .unwrap()
+            .0
+    }
+
+    fn push_body(from_history_id: Option<i32>) -> Value {
+        let digest = "aa".repeat(32);
+        serde_json::json!({
+            "url": "file:///home/user/ichno.db",
+            "source_id": "2020-01-01T00:00:00",
+            "from_history_id": from_history_id,
+            "histories": [{
+                "id": 1, "workspace_id": 1, "group_id": 1, "path": "a.txt", "version": 1, "status": 1,
+                "mtime": "2020-01-01T00:00:00", "footprint_id": 1, "digest": digest,
+                "created_at": "2020-01-01T00:00:00", "updated_at": "2020-01-01T00:00:00",
+            }],
+            "footprints": [{ "id": 1, "digest": digest, "size": 1, "fast_digest": 0, "created_at": "2020-01-01T00:00:00" }],
+        })
+    }
+
+    // The built-in test attribute, as `#[test]` is shadowed by the async one of actix_web.
+    #[std::prelude::v1::test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
+    #[actix_rt::test]
+    async fn test_push() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pool, mut conn) = setup_db(dir.path());
+        actions::create_workspace_if_needed(&mut conn, DEFAULT_WORKSPACE_NAME, now()).unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::new(pool.clone()))
+                .app_data(Data::new(PushToken(Some("secret".to_owned()))))
+                .configure(configure),
+        )
+        .await;
+        let uri = "/default/groups/laptop/push";
+
+        for authorization in [None, Some("Bearer wrong"), Some("Bearer secret2"), Some("secret")] {
+            let mut req = test::TestRequest::post().uri(uri).set_json(push_body(None));
+            if let Some(authorization) = authorization {
+                req = req.insert_header((header::AUTHORIZATION, authorization));
+            }
+            let resp = test::call_service(&app, req.to_request()).await;
+            assert_eq!(StatusCode::UNAUTHORIZED, resp.status());
+        }
+
+        let req = test::TestRequest::post()
+            .uri(uri)
+            .insert_header((header::AUTHORIZATION, "Bearer secret"))
+            .set_json(push_body(None))
+            .to_request();
+        let resp: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(Value::from(1), resp["pulled_history_id"]);
+        assert_eq!(Value::from(1), resp["histories"]);
+
+        let req =
+            test::TestRequest::get().uri(uri).insert_header((header::AUTHORIZATION, "Bearer secret")).to_request();
+        let resp: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(Value::from(1), resp["pulled_history_id"]);
+
+        // Replaying the changeset from the old cursor is rejected with the current one.
+        let req = test::TestRequest::post()
+            .uri(uri)
+            .insert_header((header::AUTHORIZATION, "Bearer secret"))
+            .set_json(push_body(None))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(StatusCode::CONFLICT, resp.status());
+        let resp: Value = test::read_body_json(resp).await;
+        assert_eq!(Value::from("pulled_history_id"), resp["code"]);
+        assert_eq!(Value::from("pulled_history_id"), resp["details"][0]["code"]);
+
+        let app = test::init_service(
+            App::new().app_data(Data::new(pool)).app_data(Data::new(PushToken(None))).configure(configure),
+        )
+        .await;
+        let req =
+            test::TestRequest::get().uri(uri).insert_header((header::AUTHORIZATION, "Bearer secret")).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(StatusCode::FORBIDDEN, resp.status());
+    }
+
+    #[actix_rt::test]
+    async fn test_get_attrs() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pool, mut conn) = setup_db(dir.path());
+        let workspace = actions::create_workspace_if_needed(&mut conn, DEFAULT_WORKSPACE_NAME, now()).unwrap();
+        let url = Url::parse("file:///tmp/ichno.db").unwrap();
+        let group =
+            actions::create_group_if_needed(&mut conn, &workspace, "g", &url, GroupType::Remote, now()).unwrap();
+        let a = create_file(&mut conn, &group, "a.txt", &"aa".repeat(32));
+        let b = create_file(&mut conn, &group, "b.txt", &"bb".repeat(32));
+        set_attr(&mut conn, &workspace, &a, "lines", "2");
+        let attr = set_attr(&mut conn, &workspace, &b, "lines", "3");
+
+        // Move the value of b's attr out to the content store.
+        let value_path = dir.path().join("value");
+        std::fs::write(&value_path, "3").unwrap();
+        conn.batch_execute(&format!(
+            "UPDATE contents SET location = '{}', body = X'' WHERE footprint_id = {}",
+            Url::from_file_path(&value_path).unwrap(),
+            attr.value_footprint_id
+        ))
+        .unwrap();
+
+        let app = test::init_service(App::new().app_data(Data::new(pool)).configure(configure)).await;
+
+        let req = test::TestRequest::get().uri("/default/attrs?key=lines").to_request();
+        let resp: Value = test::call_and_read_body_json(&app, req).await;
+        let values: Vec<_> = resp["attrs"].as_array().unwrap().iter().map(|a| a["value"].clone()).collect();
+        assert_eq!(vec![Value::from(2), Value::from(3)], values);
+        for footprint in [&a, &b] {
+            let stats = resp["stats"][&footprint.digest].as_array().unwrap();
+            assert_eq!(1, stats.len());
+        }
+        assert_eq!("b.txt", resp["stats"][&b.digest][0]["path"]);
+
+        let req = test::TestRequest::get().uri(&format!("/default/footprints/{}/attrs", &b.digest)).to_request();
+        let resp: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(Value::from(3), resp["attrs"][0]["value"]);
+        assert_eq!("b.txt", resp["stats"][0]["path"]);
+
+        let req = test::TestRequest::get().uri("/default/attrs?key=lines&limit=1").to_request();
+        let resp: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(Value::from(2), resp["attrs"][0]["value"]);
+        let next = resp["next"].as_str().unwrap();
+        assert!(next.starts_with("/default/attrs?"));
+        let req = test::TestRequest::get().uri(next).to_request();
+        let resp: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(1, resp["attrs"].as_array().unwrap().len());
+        assert_eq!(Value::from(3), resp["attrs"][0]["value"]);
+        assert!(resp["next"].is_null());
+
+        for limit in ["0", "-1", "1000000"] {
+            let req = test::TestRequest::get().uri(&format!("/default/attrs?key=lines&limit={}", limit)).to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(StatusCode::BAD_REQUEST, resp.status());
+        }
+
+        let req = test::TestRequest::get().uri("/missing/attrs?key=lines").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(StatusCode::NOT_FOUND, resp.status());
+    }
+
+    #[actix_rt::test]
+    async fn test_get_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pool, mut conn) = setup_db(dir.path());
+        let workspace = actions::create_workspace_if_needed(&mut conn, DEFAULT_WORKSPACE_NAME, now()).unwrap();
+        let url = Url::parse("file:///tmp/ichno.db").unwrap();
+        let group =
+            actions::create_group_if_needed(&mut conn, &workspace, "g", &url, GroupType::Remote, now()).unwrap();
+        for (i, path) in ["a.txt", "b.txt", "c.txt"].iter().enumerate() {
+            create_file(&mut conn, &group, path, &format!("{:02}", i).repeat(32));
+        }
+        let later = now() + chrono::Duration::days(1);
+        for (path, digest) in [("a.txt", "aa"), ("d.txt", "dd")] {
+            let footprint = actions::create_footprint_if_needed(&mut conn, &digest.repeat(32), 1, 0, later).unwrap();
+            actions::update_stat_with_footprint_if_needed(&mut conn, &group, path, &footprint, later, later).unwrap();
+        }
+
+        let app = test::init_service(App::new().app_data(Data::new(pool)).configure(configure)).await;
+        let app = &app;
+        let fetch_all = |uri: &str, key: &'static str| {
+            let mut uri = uri.to_owned();
+            async move {
+                let mut items = vec![];
+                loop {
+                    let req = test::TestRequest::get().uri(&uri).to_request();
+                    let resp: Value = test::call_and_read_body_json(app, req).await;
+                    let page = resp[key].as_array().unwrap();
+                    assert!(page.len() <= 2);
+                    items.extend(page.iter().map(|s| (s["path"].clone(), s["digest"].clone())));
+                    match resp["next"].as_str() {
+                        Some(next) => uri = next.to_owned(),
+                        None => break,
+                    }
+                }
+                items
+            }
+        };
+
+        for order in ["path", "-updated_at", "size"] {
+            let mut paths: Vec<_> = fetch_all(&format!("/default/stats/g?order={}&limit=2", order), "stats")
+                .await
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect();
+            paths.sort_by_key(|p| p.as_str().unwrap().to_owned());
+            assert_eq!(vec!["a.txt", "b.txt", "c.txt", "d.txt"], paths);
+        }
+
+        let histories = fetch_all("/default/stats/g?as_of=2020-01-01T12:00:00&limit=2", "histories").await;
+        let expected: Vec<_> = ["a.txt", "b.txt", "c.txt"]
+            .iter()
+            .enumerate()
+            .map(|(i, path)| (Value::from(*path), Value::from(format!("{:02}", i).repeat(32))))
+            .collect();
+        assert_eq!(expected, histories);
+
+        let req = test::TestRequest::get().uri("/default/stats/g?as_of=2020-01-01T12:00:00&order=path").to_request();
+        let resp = test::call_service(app, req).await;
+        assert_eq!(StatusCode::BAD_REQUEST, resp.status());
+    }
+
+    #[actix_rt::test]
+    async fn test_get_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pool, mut conn) = setup_db(dir.path());
+        let workspace = actions::create_workspace_if_needed(&mut conn, DEFAULT_WORKSPACE_NAME, now()).unwrap();
+        let url = Url::parse("file:///tmp/ichno.db").unwrap();
+        let group =
+            actions::create_group_if_needed(&mut conn, &workspace, "g", &url, GroupType::Remote, now()).unwrap();
+        for (i, path) in ["src/foo_bar.rs", "src/foo/bar.rs", "docs/foo.md", "lib/baz.rs"].iter().enumerate() {
+            create_file(&mut conn, &group, path, &format!("{:02}", i).repeat(32));
+        }
+        assert_eq!(vec!["src", "foo", "bar", "rs"], tokenize_path(" src/foo_bar.rs "));
+
+        let app = test::init_service(App::new().app_data(Data::new(pool)).configure(configure)).await;
+        let search = |q: &str| {
+            let req = test::TestRequest::get().uri(&format!("/default/search?q={}", q)).to_request();
+            async {
+                let resp: Value = test::call_and_read_body_json(&app, req).await;
+                let hits = resp["hits"].as_array().unwrap().clone();
+                assert!(hits.windows(2).all(|w| w[0]["score"].as_f64() >= w[1]["score"].as_f64()));
+                let mut paths: Vec<_> = hits.iter().map(|h| h["path"].as_str().unwrap().to_owned()).collect();
+                paths.sort();
+                paths
+            }
+        };
+        assert_eq!(vec!["src/foo/bar.rs", "src/foo_bar.rs"], search("foo%20bar").await);
+        assert_eq!(vec!["docs/foo.md", "src/foo/bar.rs", "src/foo_bar.rs"], search("fo").await);
+
+        conn.batch_execute("UPDATE stats SET path = 'lib/foo_baz.rs' WHERE path = 'lib/baz.rs'").unwrap();
+        assert_eq!(vec!["lib/foo_baz.rs"], search("foo.baz").await);
+        conn.batch_execute("UPDATE stats SET status = 0 WHERE path = 'docs/foo.md'").unwrap();
+        assert_eq!(vec!["lib/foo_baz.rs", "src/foo/bar.rs", "src/foo_bar.rs"], search("foo").await);
+
+        let req = test::TestRequest::get().uri("/default/search?q=%20").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(StatusCode::BAD_REQUEST, resp.status());
+    }
+}
@@ -1,6 +1,7 @@
 use chrono::NaiveDateTime;
-use ichnome::{Group, History, Stat, Workspace};
+use ichnome::{Attr, ContentType, Group, History, Stat, Status, Workspace};
 use serde::Serialize;
+use serde_json::Value;
 
 #[derive(Serialize)]
 pub struct WebStat {
@@ -11,7 +12,7 @@ pub struct WebStat {
     pub path: String,
 
     pub version: i32,
-    pub status: i32,
+    pub status: Status,
     pub mtime: Option<NaiveDateTime>,
 
     pub digest: Option<String>,
@@ -54,7 +55,7 @@ pub struct WebHistory {
     pub path: String,
     pub version: i32,
 
-    pub status: i32,
+    pub status: Status,
     pub mtime: Option<NaiveDateTime>,
     pub digest: Option<String>,
 
@@ -81,3 +82,51 @@ impl WebHistory {
         }
     }
 }
+
+#[derive(Serialize)]
+pub struct WebAttr {
+    pub id: i32,
+
+    pub workspace_name: String,
+    pub target_digest: String,
+    pub key: String,
+
+    pub value_digest: String,
+    pub value_content_type: ContentType,
+    pub value_summary: Option<String>,
+    pub value: Option<Value>,
+    pub status: Status,
+
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl WebAttr {
+    pub(crate) fn from(w: &Workspace, a: &Attr, body: Option<&[u8]>) -> Self {
+        let value = body.and_then(|body| {
+            if a.value_content_type == ContentType::Json {
+                serde_json::from_slice(body).ok()
+            } else if a.value_content_type == ContentType::Text {
+                String::from_utf8(body.to_vec()).ok().map(Value::String)
+            } else {
+                None
+            }
+        });
+        WebAttr {
+            id: a.id,
+
+            workspace_name: w.name.clone(),
+            target_digest: a.target_digest.clone(),
+            key: a.key.clone(),
+
+            value_digest: a.value_digest.clone(),
+            value_content_type: a.value_content_type,
+            value_summary: a.value_summary.clone(),
+            value,
+            status: a.status,
+
+            created_at: a.created_at,
+            updated_at: a.updated_at,
+        }
+    }
+}
@@ -6,9 +6,7 @@ use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields};
 #[derive(FromDeriveInput)]
 #[darling(attributes(optional), forward_attrs(allow, doc, cfg, diesel))]
 struct DeriveInputOption {
-    #[darling(default)]
     name: Option<String>,
-    #[darling(default)]
     derive: Option<String>,
     attrs: Vec<syn::Attribute>,
 }
@@ -16,12 +14,9 @@ struct DeriveInputOption {
 #[derive(FromField)]
 #[darling(attributes(optional), forward_attrs(allow, doc, cfg, diesel))]
 struct FieldOption {
-    #[darling(default)]
     name: Option<String>,
-    #[darling(default)]
-    skip: bool,
-    #[darling(default)]
-    required: bool,
+    skip: Option<bool>,
+    required: Option<bool>,
     attrs: Vec<syn::Attribute>,
 }
 
@@ -38,7 +33,7 @@ pub fn derive_options(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 }
 
 fn new_struct(input: &DeriveInput) -> TokenStream {
-    let option = DeriveInputOption::from_derive_input(&input).unwrap();
+    let option = DeriveInputOption::from_derive_input(input).unwrap();
     let name = &input.ident;
     let attrs = &option.attrs;
     let vis = &input.vis;
@@ -54,7 +49,7 @@ fn new_struct(input: &DeriveInput) -> TokenStream {
 
     let new_name = option.name.map(|n| syn::Ident::from_string(&n).unwrap()).unwrap_or(format_ident!("{}Opt", name));
     let new_fields = process_new_struct_fields(&input.data);
-    let impl_from = impl_from(&input);
+    let impl_from = impl_from(input);
 
     quote! {
         #[derive(#(#derives, )*)]
@@ -71,57 +66,49 @@ fn process_new_struct_fields(data: &Data) -> TokenStream {
     match data {
         Data::Struct(ref data) => match data.fields {
             Fields::Named(ref fields) => {
-                let new_fields = fields
-                    .named
-                    .iter()
-                    .map(|f| {
-                        let option = FieldOption::from_field(f).unwrap();
-                        if option.skip {
-                            return None;
-                        }
-                        let vis = &f.vis;
-                        let attrs = &option.attrs;
-                        let name = &option.name.map(|n| syn::Ident::from_string(&n).unwrap());
-                        let name = name.as_ref().or(f.ident.as_ref());
-                        let ty = &f.ty;
-                        let new_ty = if option.required {
-                            quote! {#ty}
-                        } else {
-                            quote! {::std::option::Option<#ty>}
-                        };
-                        Some(quote_spanned! {f.span()=>
-                            #(#attrs)*
-                            #vis #name: #new_ty
-                        })
+                let new_fields = fields.named.iter().filter_map(|f| {
+                    let option = FieldOption::from_field(f).unwrap();
+                    if option.skip.unwrap_or(false) {
+                        return None;
+                    }
+                    let vis = &f.vis;
+                    let attrs = &option.attrs;
+                    let name = &option.name.map(|n| syn::Ident::from_string(&n).unwrap());
+                    let name = name.as_ref().or(f.ident.as_ref());
+                    let ty = &f.ty;
+                    let new_ty = if option.required.unwrap_or(false) {
+                        quote! {#ty}
+                    } else {
+                        quote! {::std::option::Option<#ty>}
+                    };
+                    Some(quote_spanned! {f.span()=>
+                        #(#attrs)*
+                        #vis #name: #new_ty
                     })
-                    .filter_map(|x| x);
+                });
                 quote! {
                     #(#new_fields ,)*
                 }
             }
             Fields::Unnamed(ref fields) => {
-                let new_fields = fields
-                    .unnamed
-                    .iter()
-                    .map(|f| {
-                        let option = FieldOption::from_field(f).unwrap();
-                        if option.skip {
-                            return None;
-                        }
-                        let vis = &f.vis;
-                        let attrs = &f.attrs;
-                        let ty = &f.ty;
-                        let new_ty = if option.required {
-                            quote! {#ty}
-                        } else {
-                            quote! {::std::option::Option<#ty>}
-                        };
-                        Some(quote_spanned! {f.span()=>
-                            #(#attrs)*
-                            #vis #new_ty
-                        })
+                let new_fields = fields.unnamed.iter().filter_map(|f| {
+                    let option = FieldOption::from_field(f).unwrap();
+                    if option.skip.unwrap_or(false) {
+                        return None;
+                    }
+                    let vis = &f.vis;
+                    let attrs = &f.attrs;
+                    let ty = &f.ty;
+                    let new_ty = if option.required.unwrap_or(false) {
+                        quote! {#ty}
+                    } else {
+                        quote! {::std::option::Option<#ty>}
+                    };
+                    Some(quote_spanned! {f.span()=>
+                        #(#attrs)*
+                        #vis #new_ty
                     })
-                    .filter_map(|x| x);
+                });
                 quote! {
                     #(#new_fields ,)*
                 }
@@ -135,7 +122,7 @@ fn process_new_struct_fields(data: &Data) -> TokenStream {
 }
 
 fn impl_from(input: &DeriveInput) -> TokenStream {
-    let option = DeriveInputOption::from_derive_input(&input).unwrap();
+    let option = DeriveInputOption::from_derive_input(input).unwrap();
     let generics = &input.generics;
     let name = &input.ident;
     let (impl_g, ty_g, where_c) = generics.split_for_impl();
@@ -156,53 +143,44 @@ fn process_impl_from_fields(data: &Data) -> TokenStream {
     match data {
         Data::Struct(ref data) => match data.fields {
             Fields::Named(ref fields) => {
-                let new_fields = fields
-                    .named
-                    .iter()
-                    .map(|f| {
-                        let option = FieldOption::from_field(f).unwrap();
-                        if option.skip {
-                            return None;
-                        }
-                        let old_ident = f.ident.as_ref();
-                        let name = &option.name.map(|n| syn::Ident::from_string(&n).unwrap());
-                        let name = name.as_ref().or(old_ident);
-                        if option.required {
-                            Some(quote_spanned! {f.span()=>
-                                #name: src.#old_ident
-                            })
-                        } else {
-                            Some(quote_spanned! {f.span()=>
-                                #name: ::std::option::Option::Some(src.#old_ident)
-                            })
-                        }
-                    })
-                    .filter_map(|x| x);
+                let new_fields = fields.named.iter().filter_map(|f| {
+                    let option = FieldOption::from_field(f).unwrap();
+                    if option.skip.unwrap_or(false) {
+                        return None;
+                    }
+                    let old_ident = f.ident.as_ref();
+                    let name = &option.name.map(|n| syn::Ident::from_string(&n).unwrap());
+                    let name = name.as_ref().or(old_ident);
+                    if option.required.unwrap_or(false) {
+                        Some(quote_spanned! {f.span()=>
+                            #name: src.#old_ident
+                        })
+                    } else {
+                        Some(quote_spanned! {f.span()=>
+                            #name: ::std::option::Option::Some(src.#old_ident)
+                        })
+                    }
+                });
                 quote! {
                     #(#new_fields ,)*
                 }
             }
             Fields::Unnamed(ref fields) => {
-                let new_fields = fields
-                    .unnamed
-                    .iter()
-                    .enumerate()
-                    .map(|(i, f)| {
-                        let option = FieldOption::from_field(f).unwrap();
-                        if option.skip {
-                            return None;
-                        }
-                        if option.required {
-                            Some(quote_spanned! {f.span()=>
-                                src.#i
-                            })
-                        } else {
-                            Some(quote_spanned! {f.span()=>
-                                ::std::option::Option::Some(src.#i)
-                            })
-                        }
-                    })
-                    .filter_map(|x| x);
+                let new_fields = fields.unnamed.iter().enumerate().map(|(i, f)| {
+                    let option = FieldOption::from_field(f).unwrap();
+                    if option.skip.unwrap_or(false) {
+                        return None;
+                    }
+                    if option.required.unwrap_or(false) {
+                        Some(quote_spanned! {f.span()=>
+                            src.#i
+                        })
+                    } else {
+                        Some(quote_spanned! {f.span()=>
+                            ::std::option::Option::Some(src.#i)
+                        })
+                    }
+                });
                 quote! {
                     #(#new_fields ,)*
                 }
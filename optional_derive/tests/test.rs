@@ -29,7 +29,7 @@ fn test_simple() {
 fn test_complex() {
     let _s2 = S2 { x: 42, y: Some(42), z: true, w: "hello" };
     let s2_opt = OptionalS2 { x: true, z: Some(42), w: Some("hello") };
-    format!("{:?}", s2_opt);
+    let _ = format!("{:?}", s2_opt);
     assert_eq!(OptionalS2 { x: true, z: Some(42), w: Some("hello") }, s2_opt);
 }
 
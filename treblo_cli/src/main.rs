@@ -153,8 +153,8 @@ fn main() {
                 return;
             }
             let object_type = if is_tree { "tree" } else { "blob" };
-            let path = if path_is_default { p.strip_prefix(&base_path).unwrap() } else { p };
-            let path = if path.to_str().map_or(false, |p| p.is_empty()) { base_path.as_ref() } else { path };
+            let path = if path_is_default { p.strip_prefix(base_path).unwrap() } else { p };
+            let path = if path.to_str().is_some_and(|p| p.is_empty()) { base_path.as_ref() } else { path };
             let depth = path.iter().count();
             if !opt.show_self && !opt.summarize && is_tree && p == base_path {
                 return;
@@ -179,7 +179,7 @@ fn main() {
                         };
                         serde_json::to_vec(&record).unwrap()
                     };
-                    record_json.push('\n' as u8);
+                    record_json.push(b'\n');
                     let out = stdout();
                     let mut lock = out.lock();
                     lock.write_all(&record_json).unwrap();